@@ -37,6 +37,8 @@ pub fn main() -> opener::Result<()> {
             max_bounces: 5,
             direct_lighting: false,
             soft_shadows: false,
+            filter: Filter::default(),
+            sampling: Sampling::default(),
         },
     );
     img.save("basic.png").expect("cannot save output image");
@@ -0,0 +1,70 @@
+use geo::{util::opener, Vec3};
+
+use buzz::*;
+
+fn main() -> opener::Result<()> {
+    let camera = Camera::look_at(
+        Vec3::new(0.5, -1.4, 0.5),
+        Vec3::new(0.5, 0.5, 0.5),
+        Vec3::new(0.0, 0.0, 1.0),
+        40.0,
+    );
+
+    let white = Material::lambertian(Vec3::new(0.73, 0.73, 0.73));
+    let red = Material::lambertian(Vec3::new(0.65, 0.05, 0.05));
+    let green = Material::lambertian(Vec3::new(0.12, 0.45, 0.15));
+
+    let x = Vec3::new(1.0, 0.0, 0.0);
+    let y = Vec3::new(0.0, 1.0, 0.0);
+    let z = Vec3::new(0.0, 0.0, 1.0);
+
+    let mut objects = SceneObjects::new();
+
+    // floor, ceiling and back wall
+    objects.push(SimpleObject::new(
+        QuadGeometry::new(Vec3::zero(), x, y),
+        white.clone(),
+    ));
+    objects.push(SimpleObject::new(QuadGeometry::new(z, y, x), white.clone()));
+    objects.push(SimpleObject::new(QuadGeometry::new(y, x, z), white.clone()));
+
+    // side walls
+    objects.push(SimpleObject::new(
+        QuadGeometry::new(Vec3::zero(), y, z),
+        red,
+    ));
+    objects.push(SimpleObject::new(QuadGeometry::new(x, z, y), green));
+
+    objects.push(SimpleObject::new(
+        DiskGeometry::new(Vec3::new(0.5, 0.5, 0.999), -z, 0.15),
+        Material::light(Vec3::new(35.0, 35.0, 35.0)),
+    ));
+    objects.push(SimpleObject::new(
+        SphereGeometry::new(Vec3::new(0.3, 0.6, 0.2), 0.2),
+        white,
+    ));
+    objects.push(SimpleObject::new(
+        SphereGeometry::new(Vec3::new(0.7, 0.35, 0.15), 0.15),
+        Material::dielectric(1.5),
+    ));
+
+    let scene = Scene::new(objects, Environment::Color(Vec3::zero()));
+
+    let img = parallel_render(
+        &camera,
+        &scene,
+        &RenderConfig {
+            width: 400,
+            height: 400,
+            samples: 50,
+            max_bounces: 5,
+            direct_lighting: true,
+            soft_shadows: true,
+            filter: Filter::default(),
+            sampling: Sampling::default(),
+        },
+    );
+    img.save("cornell.png").expect("cannot save output image");
+
+    opener::open("cornell.png")
+}
@@ -21,7 +21,9 @@ pub fn main() -> opener::Result<()> {
     );
 
     let rounded_cube = csg::Sphere::new(0.65).intersection(csg::Cube::new(Vec3::replicate(1.0)));
-    let cylinder = csg::Cylinder::new(0.25, 1.1);
+    // the holes are painted with their own material
+    let cylinder =
+        csg::Cylinder::new(0.25, 1.1).with_material(Material::lambertian(Vec3::new(0.8, 0.5, 0.1)));
     let cylinder_a = cylinder.clone().transformed(Mat4::rotate(
         Vec3::new(1.0, 0.0, 0.0),
         90.0_f64.to_radians(),
@@ -70,6 +72,8 @@ pub fn main() -> opener::Result<()> {
             samples: 10,
             direct_lighting: true,
             soft_shadows: true,
+            filter: Filter::default(),
+            sampling: Sampling::default(),
         },
     );
     img.save("csg.png").expect("cannot save output image");
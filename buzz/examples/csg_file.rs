@@ -0,0 +1,72 @@
+use std::f64::consts::FRAC_PI_2;
+use std::path::Path;
+
+use geo::{mat4::Mat4, util::opener, Vec3};
+
+use buzz::{
+    csg::{self, SignedDistanceFunction},
+    *,
+};
+
+pub fn main() -> opener::Result<()> {
+    let plane = SimpleObject::new(
+        PlaneGeometry::new(Vec3::zero(), Vec3::new(0.0, 0.0, 1.0)),
+        Material::lambertian(Vec3::new(1.0, 1.0, 1.0)),
+    );
+
+    let light1 = SimpleObject::new(
+        SphereGeometry::new(Vec3::new(0.0, -1.0, 1.0).normalized() * 5.0, 0.25),
+        Material::light(Vec3::new(0.4, 0.4, 0.4)),
+    );
+    let light2 = SimpleObject::new(
+        SphereGeometry::new(Vec3::new(-1.0, 1.0, 1.0).normalized() * 5.0, 0.25),
+        Material::light(Vec3::new(0.4, 0.4, 0.4)),
+    );
+
+    let rook = csg::sexpr::load(
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("..")
+            .join("data")
+            .join("rook.csg"),
+    )
+    .expect("cannot load rook.csg");
+
+    // the rook is modeled with Y as the up axis
+    let rook = SimpleObject::new(
+        SdfGeometry::new(rook.transformed(Mat4::rotate(Vec3::new(1.0, 0.0, 0.0), -FRAC_PI_2))),
+        Material::lambertian(Vec3::new(0.55, 0.35, 0.2)),
+    );
+
+    let mut objects = SceneObjects::new();
+    objects.push(light1);
+    objects.push(light2);
+    objects.push(plane);
+    objects.push(rook);
+
+    let scene = Scene::new(objects, Environment::Color(Vec3::zero()));
+
+    let camera = Camera::look_at(
+        Vec3::new(-4.0, -1.0, 2.5),
+        Vec3::new(0.0, 0.0, 0.9),
+        Vec3::new(0.0, 0.0, 1.0),
+        35.0,
+    );
+
+    let img = parallel_render(
+        &camera,
+        &scene,
+        &RenderConfig {
+            width: 800,
+            height: 800,
+            max_bounces: 5,
+            samples: 10,
+            direct_lighting: true,
+            soft_shadows: true,
+            filter: Filter::default(),
+            sampling: Sampling::default(),
+        },
+    );
+    img.save("csg_file.png").expect("cannot save output image");
+
+    opener::open("csg_file.png")
+}
@@ -52,6 +52,8 @@ pub fn main() -> opener::Result<()> {
             samples: 10,
             direct_lighting: true,
             soft_shadows: true,
+            filter: Filter::default(),
+            sampling: Sampling::default(),
         },
     );
     img.save("cylinders.png").expect("cannot save output image");
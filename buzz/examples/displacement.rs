@@ -0,0 +1,87 @@
+use geo::{mat4::Mat4, util::opener, Vec3};
+
+use buzz::{
+    csg::{self, SignedDistanceFunction},
+    *,
+};
+
+pub fn main() -> opener::Result<()> {
+    let mut objects = SceneObjects::new();
+    objects.push(SimpleObject::new(
+        PlaneGeometry::new(Vec3::new(0.0, 0.0, -1.0), Vec3::new(0.0, 0.0, 1.0)),
+        Material::lambertian(Vec3::new(0.8, 0.8, 0.8)),
+    ));
+    objects.push(SimpleObject::new(
+        SphereGeometry::new(Vec3::new(-3.0, -3.0, 5.0), 1.0),
+        Material::light(Vec3::new(3.0, 3.0, 3.0)),
+    ));
+
+    // procedural bumps on a sphere ray marched as an sdf
+    let bumps = |p: &Vec3| (p.x * 8.0).sin() * (p.y * 8.0).sin() * (p.z * 8.0).sin() * 0.08;
+    let sdf = csg::Sphere::new(0.9)
+        .displaced(bumps, 0.08, 0.08 * 8.0 * 3.0_f64.sqrt())
+        .transformed(Mat4::translate(Vec3::new(0.0, -1.2, 0.0)));
+    objects.push(SimpleObject::new(
+        SdfGeometry::new(sdf),
+        Material::lambertian(Vec3::new(0.8, 0.3, 0.2)),
+    ));
+
+    // an octahedron subdivided and displaced to be a ridged sphere
+    let center = Vec3::new(0.0, 1.2, 0.0);
+    let octahedron = TriangleMesh::new(
+        vec![
+            center + Vec3::new(1.0, 0.0, 0.0),
+            center + Vec3::new(-1.0, 0.0, 0.0),
+            center + Vec3::new(0.0, 1.0, 0.0),
+            center + Vec3::new(0.0, -1.0, 0.0),
+            center + Vec3::new(0.0, 0.0, 1.0),
+            center + Vec3::new(0.0, 0.0, -1.0),
+        ],
+        vec![
+            [0, 2, 4],
+            [2, 1, 4],
+            [1, 3, 4],
+            [3, 0, 4],
+            [2, 0, 5],
+            [1, 2, 5],
+            [3, 1, 5],
+            [0, 3, 5],
+        ],
+        Material::lambertian(Vec3::new(0.2, 0.4, 0.8)),
+    );
+    objects.push(octahedron.displaced(6, |p, _| {
+        // move the vertices on a sphere whose radius changes with the height
+        let d = p - center;
+        let radius = 0.9 + (d.normalized().z * 20.0).sin().abs() * 0.05;
+
+        radius - d.norm()
+    }));
+
+    let scene = Scene::new(objects, Environment::Color(Vec3::new(0.1, 0.1, 0.1)));
+
+    let camera = Camera::look_at(
+        Vec3::new(-5.0, 0.0, 2.0),
+        Vec3::zero(),
+        Vec3::new(0.0, 0.0, 1.0),
+        40.0,
+    );
+
+    let img = parallel_render(
+        &camera,
+        &scene,
+        &RenderConfig {
+            width: 640,
+            height: 480,
+            max_bounces: 5,
+            samples: 10,
+            direct_lighting: true,
+            soft_shadows: true,
+            filter: Filter::default(),
+            sampling: Sampling::default(),
+        },
+    );
+    img.save("displacement.png")
+        .expect("cannot save output image");
+
+    opener::open("displacement.png")
+}
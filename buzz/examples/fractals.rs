@@ -0,0 +1,69 @@
+use geo::{mat4::Mat4, util::opener, Vec3};
+
+use buzz::{
+    csg::{self, SignedDistanceFunction},
+    *,
+};
+
+pub fn main() -> opener::Result<()> {
+    let mut objects = SceneObjects::new();
+    objects.push(SimpleObject::new(
+        PlaneGeometry::new(Vec3::new(0.0, 0.0, -1.0), Vec3::new(0.0, 0.0, 1.0)),
+        Material::lambertian(Vec3::new(0.8, 0.8, 0.8)),
+    ));
+    objects.push(SimpleObject::new(
+        SphereGeometry::new(Vec3::new(-4.0, -3.0, 6.0), 1.5),
+        Material::light(Vec3::new(2.0, 2.0, 2.0)),
+    ));
+
+    // the fractals are colored by how close their orbits get to the origin,
+    // a bigger epsilon smooths the details of the mandelbulb that are smaller
+    // than a pixel
+    objects.push(SimpleObject::new(
+        SdfGeometry::new(
+            csg::Mandelbulb::new(8.0, 10).transformed(Mat4::translate(Vec3::new(0.0, 0.0, 0.1))),
+        )
+        .with_epsilon(2e-4),
+        Material::orbit_trap(Vec3::new(0.9, 0.3, 0.1), Vec3::new(0.9, 0.8, 0.5)),
+    ));
+    objects.push(SimpleObject::new(
+        SdfGeometry::new(
+            csg::MengerSponge::new(4).transformed(Mat4::translate(Vec3::new(1.0, -2.8, 0.0))),
+        ),
+        Material::orbit_trap(Vec3::new(0.1, 0.2, 0.6), Vec3::new(0.7, 0.8, 0.9)),
+    ));
+    objects.push(SimpleObject::new(
+        SdfGeometry::new(
+            csg::SierpinskiTetrahedron::new(8)
+                .transformed(Mat4::translate(Vec3::new(1.0, 2.8, 0.0))),
+        ),
+        Material::orbit_trap(Vec3::new(0.1, 0.5, 0.2), Vec3::new(0.8, 0.9, 0.6)),
+    ));
+
+    let scene = Scene::new(objects, Environment::Color(Vec3::new(0.1, 0.1, 0.1)));
+
+    let camera = Camera::look_at(
+        Vec3::new(-8.0, 0.0, 3.0),
+        Vec3::new(0.5, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, 1.0),
+        50.0,
+    );
+
+    let img = parallel_render(
+        &camera,
+        &scene,
+        &RenderConfig {
+            width: 640,
+            height: 480,
+            max_bounces: 5,
+            samples: 10,
+            direct_lighting: true,
+            soft_shadows: true,
+            filter: Filter::default(),
+            sampling: Sampling::default(),
+        },
+    );
+    img.save("fractals.png").expect("cannot save output image");
+
+    opener::open("fractals.png")
+}
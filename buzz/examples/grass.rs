@@ -0,0 +1,87 @@
+use rand::prelude::*;
+use rand_xorshift::XorShiftRng;
+
+use geo::{primitive::polyline::Polyline, util::opener, Vec3};
+
+use buzz::*;
+
+pub fn main() -> opener::Result<()> {
+    let mut rng = XorShiftRng::seed_from_u64(42);
+
+    let blades = (0..20_000).map(|_| {
+        let root = Vec3::new(rng.gen_range(-3.0..3.0), rng.gen_range(-3.0..3.0), 0.0);
+        let height = rng.gen_range(0.2..0.5);
+        let bend = Vec3::new(rng.gen_range(-0.2..0.2), rng.gen_range(-0.2..0.2), 0.0);
+
+        Curve::bezier(
+            [
+                root,
+                root + Vec3::new(0.0, 0.0, height * 0.5),
+                root + bend * 0.5 + Vec3::new(0.0, 0.0, height * 0.9),
+                root + bend + Vec3::new(0.0, 0.0, height),
+            ],
+            (0.01, 0.001),
+            6,
+        )
+    });
+
+    // wire frame of a cube rendered with tubes
+    let (a, b) = (-0.4, 0.4);
+    let v = |x: f64, y: f64, z: f64| Vec3::new(x, y, z + 0.8);
+    let edges = [
+        vec![v(a, a, a), v(b, a, a), v(b, b, a), v(a, b, a), v(a, a, a)],
+        vec![v(a, a, b), v(b, a, b), v(b, b, b), v(a, b, b), v(a, a, b)],
+        vec![v(a, a, a), v(a, a, b)],
+        vec![v(b, a, a), v(b, a, b)],
+        vec![v(b, b, a), v(b, b, b)],
+        vec![v(a, b, a), v(a, b, b)],
+    ];
+    let wires = edges
+        .iter()
+        .map(|e| Curve::from_polyline(&Polyline::from(e.clone()), (0.03, 0.03)));
+
+    let mut objects = SceneObjects::new();
+    objects.push(SimpleObject::new(
+        PlaneGeometry::new(Vec3::zero(), Vec3::new(0.0, 0.0, 1.0)),
+        Material::lambertian(Vec3::new(0.3, 0.2, 0.1)),
+    ));
+    objects.push(SimpleObject::new(
+        CurvesGeometry::new(CurveKind::Ribbon, blades),
+        Material::lambertian(Vec3::new(0.2, 0.6, 0.1)),
+    ));
+    objects.push(SimpleObject::new(
+        CurvesGeometry::new(CurveKind::Tube, wires),
+        Material::metal(Vec3::new(0.8, 0.8, 0.8), 0.1),
+    ));
+    objects.push(SimpleObject::new(
+        SphereGeometry::new(Vec3::new(-2.0, -2.0, 4.0), 1.0),
+        Material::light(Vec3::new(2.0, 2.0, 2.0)),
+    ));
+
+    let scene = Scene::new(objects, Environment::Color(Vec3::new(0.5, 0.7, 0.9)));
+
+    let camera = Camera::look_at(
+        Vec3::new(-3.0, -1.0, 1.2),
+        Vec3::new(0.0, 0.0, 0.5),
+        Vec3::new(0.0, 0.0, 1.0),
+        50.0,
+    );
+
+    let img = parallel_render(
+        &camera,
+        &scene,
+        &RenderConfig {
+            width: 640,
+            height: 480,
+            max_bounces: 5,
+            samples: 10,
+            direct_lighting: true,
+            soft_shadows: true,
+            filter: Filter::default(),
+            sampling: Sampling::default(),
+        },
+    );
+    img.save("grass.png").expect("cannot save output image");
+
+    opener::open("grass.png")
+}
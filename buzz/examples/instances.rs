@@ -0,0 +1,78 @@
+use std::{env, path::Path, sync::Arc};
+
+use geo::{
+    mat4::{Mat4, Transform},
+    mesh::load_mesh,
+    util::opener,
+    Vec3,
+};
+
+use buzz::*;
+
+pub fn main() -> opener::Result<()> {
+    let camera = Camera::look_at(
+        Vec3::new(0.0, -14.0, 8.0),
+        Vec3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, 1.0),
+        50.0,
+    );
+
+    let suzanne = load_mesh(
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("..")
+            .join("data")
+            .join("suzanne.stl"),
+    )
+    .expect("cannot load suzanne.stl");
+
+    // the mesh is built only once and then shared by all the instances
+    let suzanne = Arc::new(MeshGeometry::new(suzanne.triangles(), true));
+
+    let mut objects = SceneObjects::new();
+
+    for y in -5..=5 {
+        for x in -5..=5 {
+            let angle = f64::from(x * y) * 15.0;
+            let trans = Mat4::translate(Vec3::new(f64::from(x) * 1.5, f64::from(y) * 1.5, 0.5))
+                .transform(&Mat4::scale(Vec3::replicate(0.5)))
+                .transform(&Mat4::rotate(Vec3::new(0.0, 0.0, 1.0), angle.to_radians()));
+
+            let albedo = Vec3::new(f64::from(x + 5) / 10.0, 0.3, f64::from(y + 5) / 10.0);
+
+            objects.push(SimpleObject::new(
+                TransformedGeometry::new(Arc::clone(&suzanne), trans),
+                Material::lambertian(albedo),
+            ));
+        }
+    }
+
+    objects.push(SimpleObject::new(
+        PlaneGeometry::new(Vec3::zero(), Vec3::new(0.0, 0.0, 1.0)),
+        Material::lambertian(Vec3::new(0.8, 0.8, 0.8)),
+    ));
+    objects.push(SimpleObject::new(
+        SphereGeometry::new(Vec3::new(-5.0, -10.0, 10.0), 2.0),
+        Material::light(Vec3::new(0.8, 0.8, 0.8)),
+    ));
+
+    let scene = Scene::new(objects, Environment::Color(Vec3::new(0.1, 0.1, 0.1)));
+
+    let img = parallel_render(
+        &camera,
+        &scene,
+        &RenderConfig {
+            width: 800,
+            height: 450,
+            max_bounces: 5,
+            samples: 10,
+            direct_lighting: true,
+            soft_shadows: true,
+            filter: Filter::default(),
+            sampling: Sampling::default(),
+        },
+    );
+
+    img.save("instances.png").expect("cannot save output image");
+
+    opener::open("instances.png")
+}
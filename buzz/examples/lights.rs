@@ -36,6 +36,8 @@ fn main() -> opener::Result<()> {
             max_bounces: 5,
             direct_lighting: true,
             soft_shadows: true,
+            filter: Filter::default(),
+            sampling: Sampling::default(),
         },
     );
     img.save("lights.png").expect("cannot save output image");
@@ -0,0 +1,62 @@
+use geo::{util::opener, Vec3};
+
+use buzz::{
+    implicit::{ImplicitGeometry, Metaballs},
+    *,
+};
+
+pub fn main() -> opener::Result<()> {
+    let plane = SimpleObject::new(
+        PlaneGeometry::new(Vec3::new(0.0, 0.0, -0.6), Vec3::new(0.0, 0.0, 1.0)),
+        Material::lambertian(Vec3::new(1.0, 1.0, 1.0)),
+    );
+
+    let light = SimpleObject::new(
+        SphereGeometry::new(Vec3::new(-1.0, -1.0, 2.0).normalized() * 4.0, 0.5),
+        Material::light(Vec3::new(0.5, 0.5, 0.5)),
+    );
+
+    let blobs = Metaballs::new(0.3)
+        .with_ball(Vec3::new(0.0, 0.0, 0.0), 0.9, 1.0)
+        .with_ball(Vec3::new(0.0, 0.6, 0.3), 0.7, 1.0)
+        .with_ball(Vec3::new(0.0, -0.7, 0.1), 0.6, 1.0)
+        .with_ball(Vec3::new(0.0, -1.2, -0.2), 0.5, 1.0)
+        .with_ball(Vec3::new(-0.5, 0.1, 0.4), 0.5, -1.0);
+
+    let metaballs = SimpleObject::new(
+        ImplicitGeometry::new(blobs),
+        Material::lambertian(Vec3::new(0.8, 0.3, 0.2)),
+    );
+
+    let mut objects = SceneObjects::new();
+    objects.push(light);
+    objects.push(plane);
+    objects.push(metaballs);
+
+    let scene = Scene::new(objects, Environment::Color(Vec3::new(0.1, 0.1, 0.1)));
+
+    let camera = Camera::look_at(
+        Vec3::new(-3.0, 0.0, 1.0),
+        Vec3::zero(),
+        Vec3::new(0.0, 0.0, 1.0),
+        50.0,
+    );
+
+    let img = parallel_render(
+        &camera,
+        &scene,
+        &RenderConfig {
+            width: 640,
+            height: 480,
+            max_bounces: 5,
+            samples: 10,
+            direct_lighting: true,
+            soft_shadows: true,
+            filter: Filter::default(),
+            sampling: Sampling::default(),
+        },
+    );
+    img.save("metaballs.png").expect("cannot save output image");
+
+    opener::open("metaballs.png")
+}
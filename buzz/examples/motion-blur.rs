@@ -0,0 +1,52 @@
+use geo::{mat4::Mat4, util::opener, Vec3};
+
+use buzz::*;
+
+fn main() -> opener::Result<()> {
+    let target = Vec3::new(0.0, 0.0, -1.0);
+    let camera = Camera::look_at(Vec3::zero(), target, Vec3::new(0.0, 1.0, 0.0), 60.0)
+        .with_shutter(0.0, 1.0);
+
+    let mut objects = SceneObjects::new();
+    objects.push(SimpleObject::new(
+        AnimatedGeometry::new(
+            SphereGeometry::new(Vec3::new(-0.5, 0.0, -1.0), 0.3),
+            Mat4::identity(),
+            Mat4::translate(Vec3::new(0.0, 0.3, 0.0)),
+        ),
+        Material::lambertian(Vec3::new(0.8, 0.3, 0.3)),
+    ));
+    objects.push(SimpleObject::new(
+        SphereGeometry::new(Vec3::new(0.5, 0.0, -1.0), 0.3),
+        Material::lambertian(Vec3::new(0.3, 0.3, 0.8)),
+    ));
+    objects.push(SimpleObject::new(
+        SphereGeometry::new(Vec3::new(0.0, -100.5, -1.0), 100.0),
+        Material::lambertian(Vec3::new(0.8, 0.8, 0.0)),
+    ));
+    objects.push(SimpleObject::new(
+        SphereGeometry::new(Vec3::new(-0.5, 1.0, 1.0), 0.3),
+        Material::light(Vec3::new(0.5, 0.5, 0.5)),
+    ));
+
+    let scene = Scene::new(objects, Environment::Color(Vec3::zero()));
+
+    let img = parallel_render(
+        &camera,
+        &scene,
+        &RenderConfig {
+            width: 400,
+            height: 200,
+            samples: 50,
+            max_bounces: 5,
+            direct_lighting: true,
+            soft_shadows: true,
+            filter: Filter::default(),
+            sampling: Sampling::default(),
+        },
+    );
+    img.save("motion-blur.png")
+        .expect("cannot save output image");
+
+    opener::open("motion-blur.png")
+}
@@ -71,6 +71,8 @@ pub fn main() -> opener::Result<()> {
             max_bounces: 10,
             direct_lighting: true,
             soft_shadows: false,
+            filter: Filter::default(),
+            sampling: Sampling::default(),
         },
     );
     img.save("particles.png").expect("cannot save output image");
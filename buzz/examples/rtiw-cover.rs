@@ -72,6 +72,8 @@ pub fn main() -> opener::Result<()> {
             samples: 50,
             direct_lighting: false,
             soft_shadows: false,
+            filter: Filter::default(),
+            sampling: Sampling::default(),
         },
     );
     img.save("ray-tracing-in-a-weekend-cover.png")
@@ -0,0 +1,110 @@
+use std::sync::Arc;
+
+use geo::{mat4::Mat4, util::opener, Aabb, Axis, Vec3};
+
+use buzz::{
+    csg::{self, SignedDistanceFunction},
+    *,
+};
+
+pub fn main() -> opener::Result<()> {
+    let mut objects = SceneObjects::new();
+    objects.push(SimpleObject::new(
+        PlaneGeometry::new(Vec3::new(0.0, 0.0, -1.0), Vec3::new(0.0, 0.0, 1.0)),
+        Material::lambertian(Vec3::new(0.8, 0.8, 0.8)),
+    ));
+    objects.push(SimpleObject::new(
+        SphereGeometry::new(Vec3::new(-4.0, -2.0, 6.0), 1.5),
+        Material::light(Vec3::new(2.0, 2.0, 2.0)),
+    ));
+
+    // a grid of bolts on the floor
+    let bolt = csg::HexagonalPrism::new(0.1, 0.08)
+        .rounded(0.01)
+        .transformed(Mat4::rotate(
+            Vec3::new(1.0, 0.0, 0.0),
+            90.0_f64.to_radians(),
+        ));
+    objects.push(SimpleObject::new(
+        SdfGeometry::new(
+            bolt.repeated(Vec3::new(0.4, 0.4, 0.0), [5, 11, 1])
+                .transformed(Mat4::translate(Vec3::new(-1.0, -2.0, -0.95))),
+        ),
+        Material::metal(Vec3::new(0.7, 0.7, 0.7), 0.2),
+    ));
+
+    // a twisted column
+    let column = csg::RoundedBox::new(Vec3::new(0.5, 2.0, 0.5), 0.05)
+        .twisted(1.5)
+        .transformed(Mat4::rotate(
+            Vec3::new(1.0, 0.0, 0.0),
+            90.0_f64.to_radians(),
+        ));
+    // twisting doesn't preserve distances, keep track of how many steps the
+    // rays take
+    let column = Arc::new(
+        SdfGeometry::new(column.transformed(Mat4::translate(Vec3::new(0.5, -1.2, 0.0))))
+            .with_stats(),
+    );
+    objects.push(SimpleObject::new(
+        Arc::clone(&column),
+        Material::lambertian(Vec3::new(0.8, 0.6, 0.3)),
+    ));
+
+    // a hollow sphere cut in half to show the shell, mirrored twice
+    let shell = csg::Sphere::new(0.35)
+        .onion(0.05)
+        .intersection(csg::HalfSpace::new(
+            Vec3::new(0.0, 0.0, 1.0),
+            0.1,
+            Aabb::cube(Vec3::zero(), 1.0),
+        ));
+    objects.push(SimpleObject::new(
+        SdfGeometry::new(
+            shell
+                .transformed(Mat4::translate(Vec3::new(0.0, 0.5, 0.0)))
+                .mirrored(Axis::Y)
+                .elongated(Vec3::new(0.0, 0.0, 0.2))
+                .transformed(Mat4::translate(Vec3::new(0.0, 1.4, -0.4))),
+        ),
+        Material::lambertian(Vec3::new(0.3, 0.5, 0.8)),
+    ));
+
+    let scene = Scene::new(objects, Environment::Color(Vec3::new(0.1, 0.1, 0.1)));
+
+    let camera = Camera::look_at(
+        Vec3::new(-5.0, 0.0, 2.5),
+        Vec3::new(0.0, 0.0, -0.3),
+        Vec3::new(0.0, 0.0, 1.0),
+        45.0,
+    );
+
+    let img = parallel_render(
+        &camera,
+        &scene,
+        &RenderConfig {
+            width: 640,
+            height: 480,
+            max_bounces: 5,
+            samples: 10,
+            direct_lighting: true,
+            soft_shadows: true,
+            filter: Filter::default(),
+            sampling: Sampling::default(),
+        },
+    );
+    img.save("sdf_operators.png")
+        .expect("cannot save output image");
+
+    if let Some(stats) = column.stats() {
+        println!(
+            "column: {} rays, {} hits, {:.1} steps per ray, {} relaxation failures",
+            stats.rays,
+            stats.hits,
+            stats.average_steps(),
+            stats.relaxation_failures
+        );
+    }
+
+    opener::open("sdf_operators.png")
+}
@@ -0,0 +1,87 @@
+use geo::{
+    mat4::{Mat4, Transform},
+    util::opener,
+    Aabb, Vec3,
+};
+
+use buzz::{
+    csg::{self, SignedDistanceFunction},
+    *,
+};
+
+pub fn main() -> opener::Result<()> {
+    let mut objects = SceneObjects::new();
+    objects.push(SimpleObject::new(
+        PlaneGeometry::new(Vec3::new(0.0, 0.0, -0.5), Vec3::new(0.0, 0.0, 1.0)),
+        Material::lambertian(Vec3::new(0.8, 0.8, 0.8)),
+    ));
+    objects.push(SimpleObject::new(
+        SphereGeometry::new(Vec3::new(-4.0, -2.0, 6.0), 1.5),
+        Material::light(Vec3::new(2.0, 2.0, 2.0)),
+    ));
+
+    // the primitives are mostly aligned to the Y axis, rotate them so that
+    // their axis is vertical
+    let up = Mat4::rotate(Vec3::new(1.0, 0.0, 0.0), 90.0_f64.to_radians());
+    let at = |x: f64, y: f64| Mat4::translate(Vec3::new(0.0, x * 1.2, y * 1.2)).transform(&up);
+
+    let material = |i: f64| Material::lambertian(Vec3::new(0.2 + i * 0.08, 0.5, 0.9 - i * 0.08));
+
+    macro_rules! push {
+        ($i:expr, $sdf:expr) => {
+            let i = f64::from($i);
+            objects.push(SimpleObject::new(
+                SdfGeometry::new($sdf.transformed(at(i % 3.0 - 1.0, (i / 3.0).floor()))),
+                material(i),
+            ));
+        };
+    }
+
+    push!(0, csg::Torus::new(0.35, 0.12));
+    push!(
+        1,
+        csg::Capsule::new(Vec3::new(0.0, -0.3, 0.0), Vec3::new(0.0, 0.3, 0.0), 0.2)
+    );
+    push!(2, csg::Cone::new(0.8, 0.4, 0.1));
+    push!(3, csg::RoundedBox::new(Vec3::replicate(0.7), 0.1));
+    push!(4, csg::HexagonalPrism::new(0.3, 0.8));
+    push!(5, csg::TriangularPrism::new(0.4, 0.8));
+    push!(6, csg::Ellipsoid::new(Vec3::new(0.5, 0.3, 0.2)));
+    push!(7, csg::Octahedron::new(0.45));
+    push!(
+        8,
+        csg::Sphere::new(0.45).intersection(csg::HalfSpace::new(
+            Vec3::new(-1.0, 0.0, 0.0),
+            0.1,
+            Aabb::cube(Vec3::zero(), 1.0),
+        ))
+    );
+
+    let scene = Scene::new(objects, Environment::Color(Vec3::new(0.1, 0.1, 0.1)));
+
+    let camera = Camera::look_at(
+        Vec3::new(-6.0, 0.0, 3.0),
+        Vec3::new(0.0, 0.0, 1.0),
+        Vec3::new(0.0, 0.0, 1.0),
+        40.0,
+    );
+
+    let img = parallel_render(
+        &camera,
+        &scene,
+        &RenderConfig {
+            width: 640,
+            height: 480,
+            max_bounces: 5,
+            samples: 10,
+            direct_lighting: true,
+            soft_shadows: true,
+            filter: Filter::default(),
+            sampling: Sampling::default(),
+        },
+    );
+    img.save("sdf_primitives.png")
+        .expect("cannot save output image");
+
+    opener::open("sdf_primitives.png")
+}
@@ -0,0 +1,69 @@
+use geo::{
+    mat4::{Mat4, Transform},
+    util::opener,
+    Vec3,
+};
+
+use buzz::*;
+
+pub fn main() -> opener::Result<()> {
+    let mut objects = SceneObjects::new();
+    objects.push(SimpleObject::new(
+        PlaneGeometry::new(Vec3::zero(), Vec3::new(0.0, 0.0, 1.0)),
+        Material::lambertian(Vec3::new(1.0, 1.0, 1.0)),
+    ));
+    objects.push(SimpleObject::new(
+        SphereGeometry::new(Vec3::new(5.0, 5.0, 5.0), 1.0),
+        Material::light(Vec3::new(0.8, 0.8, 0.8)),
+    ));
+    objects.push(SimpleObject::new(
+        CappedCylinderGeometry::new(Vec3::new(-2.0, 0.0, 0.5), Vec3::new(-1.0, -1.0, 1.5), 0.4),
+        Material::lambertian(Vec3::new(0.31, 0.46, 0.22)),
+    ));
+    objects.push(SimpleObject::new(
+        ConeGeometry::new(Vec3::new(0.0, 0.5, 0.0), 0.6, Vec3::new(0.0, 0.5, 1.8), 0.0),
+        Material::lambertian(Vec3::new(0.88, 0.1, 0.1)),
+    ));
+    objects.push(SimpleObject::new(
+        ConeGeometry::new(Vec3::new(2.0, 0.0, 0.0), 0.6, Vec3::new(2.0, 0.0, 1.0), 0.3),
+        Material::metal(Vec3::new(0.8, 0.8, 0.8), 0.1),
+    ));
+    objects.push(SimpleObject::new(
+        TransformedGeometry::new(
+            TorusGeometry::new(0.5, 0.15),
+            Mat4::rotate(Vec3::new(1.0, 0.0, 0.0), 60.0_f64.to_radians())
+                .transform(&Mat4::translate(Vec3::new(0.0, -1.5, 0.7))),
+        ),
+        Material::lambertian(Vec3::new(0.2, 0.3, 0.8)),
+    ));
+
+    let scene = Scene::new(
+        objects,
+        Environment::LinearGradient(Vec3::new(1.0, 1.0, 1.0), Vec3::new(0.5, 0.7, 1.0)),
+    );
+
+    let camera = Camera::look_at(
+        Vec3::new(0.0, -6.0, 3.0),
+        Vec3::new(0.0, 0.0, 0.5),
+        Vec3::new(0.0, 0.0, 1.0),
+        50.0,
+    );
+
+    let img = parallel_render(
+        &camera,
+        &scene,
+        &RenderConfig {
+            width: 800,
+            height: 450,
+            max_bounces: 5,
+            samples: 20,
+            direct_lighting: true,
+            soft_shadows: true,
+            filter: Filter::default(),
+            sampling: Sampling::default(),
+        },
+    );
+    img.save("shapes.png").expect("cannot save output image");
+
+    opener::open("shapes.png")
+}
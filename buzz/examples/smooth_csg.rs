@@ -0,0 +1,65 @@
+use geo::{mat4::Mat4, util::opener, Vec3};
+
+use buzz::{
+    csg::{self, Blend, SignedDistanceFunction},
+    *,
+};
+
+pub fn main() -> opener::Result<()> {
+    let mut objects = SceneObjects::new();
+    objects.push(SimpleObject::new(
+        PlaneGeometry::new(Vec3::new(0.0, 0.0, -0.5), Vec3::new(0.0, 0.0, 1.0)),
+        Material::lambertian(Vec3::new(0.8, 0.8, 0.8)),
+    ));
+    objects.push(SimpleObject::new(
+        SphereGeometry::new(Vec3::new(-3.0, -3.0, 4.0), 1.0),
+        Material::light(Vec3::new(2.0, 2.0, 2.0)),
+    ));
+
+    // a cube with a sphere on top blended with a fillet and a cylinder carved
+    // with rounded edges
+    let base = csg::Cube::new(Vec3::new(1.2, 1.2, 0.6)).smooth_union(
+        csg::Sphere::new(0.45).transformed(Mat4::translate(Vec3::new(0.0, 0.0, 0.4))),
+        Blend::Polynomial(0.3),
+    );
+    let hole = csg::Cylinder::new(0.2, 2.0);
+    let sdf = base
+        .smooth_difference(hole, Blend::Exponential(0.03))
+        .transformed(Mat4::rotate(
+            Vec3::new(0.0, 0.0, 1.0),
+            30.0_f64.to_radians(),
+        ));
+
+    objects.push(SimpleObject::new(
+        SdfGeometry::new(sdf),
+        Material::lambertian(Vec3::new(0.3, 0.5, 0.7)),
+    ));
+
+    let scene = Scene::new(objects, Environment::Color(Vec3::new(0.1, 0.1, 0.1)));
+
+    let camera = Camera::look_at(
+        Vec3::new(-3.0, 0.0, 1.5),
+        Vec3::zero(),
+        Vec3::new(0.0, 0.0, 1.0),
+        35.0,
+    );
+
+    let img = parallel_render(
+        &camera,
+        &scene,
+        &RenderConfig {
+            width: 640,
+            height: 480,
+            max_bounces: 5,
+            samples: 10,
+            direct_lighting: true,
+            soft_shadows: true,
+            filter: Filter::default(),
+            sampling: Sampling::default(),
+        },
+    );
+    img.save("smooth_csg.png")
+        .expect("cannot save output image");
+
+    opener::open("smooth_csg.png")
+}
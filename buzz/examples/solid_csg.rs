@@ -0,0 +1,106 @@
+use std::path::Path;
+
+use geo::{
+    mat4::{Mat4, Transform},
+    mesh::load_mesh,
+    spatial_index::Shape,
+    util::opener,
+    Aabb, Vec3,
+};
+
+use buzz::*;
+
+pub fn main() -> opener::Result<()> {
+    let plane = SimpleObject::new(
+        PlaneGeometry::new(Vec3::new(0.0, 0.0, -0.5), Vec3::new(0.0, 0.0, 1.0)),
+        Material::lambertian(Vec3::new(1.0, 1.0, 1.0)),
+    );
+
+    let light1 = SimpleObject::new(
+        SphereGeometry::new(Vec3::new(0.0, -1.0, 0.25).normalized() * 4.0, 0.25),
+        Material::light(Vec3::new(0.3, 0.3, 0.3)),
+    );
+    let light2 = SimpleObject::new(
+        SphereGeometry::new(Vec3::new(-1.0, 1.0, 0.0).normalized() * 4.0, 0.25),
+        Material::light(Vec3::new(0.3, 0.3, 0.3)),
+    );
+
+    // the classic rounded cube with three holes, but with exact surfaces
+    let cylinder = || CylinderGeometry::new(0.25, (-0.6, 0.6));
+    let rotated_cylinder =
+        |axis| TransformedGeometry::new(cylinder(), Mat4::rotate(axis, 90.0_f64.to_radians()));
+
+    let rounded_cube = CubeGeometry::new(Aabb::cube(Vec3::zero(), 1.0))
+        .intersect(SphereGeometry::new(Vec3::zero(), 0.65))
+        .subtract(cylinder())
+        .subtract(rotated_cylinder(Vec3::new(1.0, 0.0, 0.0)))
+        .subtract(rotated_cylinder(Vec3::new(0.0, 1.0, 0.0)));
+
+    let rounded_cube = SimpleObject::new(
+        TransformedGeometry::new(
+            rounded_cube,
+            Mat4::translate(Vec3::new(0.0, -0.8, 0.0)).transform(&Mat4::rotate(
+                Vec3::new(0.0, 0.0, 1.0),
+                30.0_f64.to_radians(),
+            )),
+        ),
+        Material::lambertian(Vec3::new(0.31, 0.46, 0.22)),
+    );
+
+    // closed meshes are solids too
+    let cube = load_mesh(
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("..")
+            .join("data")
+            .join("cube.stl"),
+    )
+    .expect("cannot load cube.stl");
+    let cube = MeshGeometry::new(cube.triangles(), true);
+    let bbox = cube.bbox();
+
+    let carved_cube = TransformedGeometry::new(
+        cube,
+        Mat4::scale(Vec3::replicate(0.8 / bbox.dimensions().x))
+            .transform(&Mat4::translate(-bbox.center())),
+    )
+    .subtract(SphereGeometry::new(Vec3::new(-0.4, -0.4, 0.4), 0.5));
+
+    let carved_cube = SimpleObject::new(
+        TransformedGeometry::new(carved_cube, Mat4::translate(Vec3::new(0.0, 0.8, -0.1))),
+        Material::lambertian(Vec3::new(0.7, 0.3, 0.2)),
+    );
+
+    let mut objects = SceneObjects::new();
+    objects.push(light1);
+    objects.push(light2);
+    objects.push(plane);
+    objects.push(rounded_cube);
+    objects.push(carved_cube);
+
+    let scene = Scene::new(objects, Environment::Color(Vec3::zero()));
+
+    let camera = Camera::look_at(
+        Vec3::new(-3.5, 0.0, 1.5),
+        Vec3::zero(),
+        Vec3::new(0.0, 0.0, 1.0),
+        40.0,
+    );
+
+    let img = parallel_render(
+        &camera,
+        &scene,
+        &RenderConfig {
+            width: 1280,
+            height: 720,
+            max_bounces: 5,
+            samples: 10,
+            direct_lighting: true,
+            soft_shadows: true,
+            filter: Filter::default(),
+            sampling: Sampling::default(),
+        },
+    );
+    img.save("solid_csg.png").expect("cannot save output image");
+
+    opener::open("solid_csg.png")
+}
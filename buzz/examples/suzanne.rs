@@ -63,6 +63,8 @@ pub fn main() -> opener::Result<()> {
             samples: 25,
             direct_lighting: true,
             soft_shadows: true,
+            filter: Filter::default(),
+            sampling: Sampling::default(),
         },
     );
 
@@ -24,9 +24,7 @@ pub fn main() -> opener::Result<()> {
     )
     .expect("cannot load teapot.obj");
 
-    for t in teapot.triangles() {
-        objects.push(Facet::new(t, &MESH_MATERIAL, true));
-    }
+    objects.push(TriangleMesh::from_mesh(teapot.as_ref(), MESH_MATERIAL));
 
     objects.push(Box::new(SimpleObject::new(
         SphereGeometry::new(Vec3::new(2.0, 5.0, -3.0), 0.5),
@@ -51,6 +49,8 @@ pub fn main() -> opener::Result<()> {
             samples: 25,
             direct_lighting: true,
             soft_shadows: true,
+            filter: Filter::default(),
+            sampling: Sampling::default(),
         },
     );
 
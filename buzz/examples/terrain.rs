@@ -0,0 +1,57 @@
+use geo::{util::opener, Vec3};
+
+use buzz::*;
+
+pub fn main() -> opener::Result<()> {
+    let terrain = HeightfieldGeometry::from_fn((-10.0, -10.0), (10.0, 10.0), 1000, |x, y| {
+        let r = (x.powi(2) + y.powi(2)).sqrt();
+
+        (x * 0.7).sin() * (y * 0.5).cos() * 1.5
+            + (r * 2.0).cos() * 0.2
+            + (x * 3.1 + y * 2.3).sin() * 0.1
+    });
+
+    let mut objects = SceneObjects::new();
+    objects.push(SimpleObject::new(
+        terrain,
+        Material::lambertian(Vec3::new(0.4, 0.6, 0.3)),
+    ));
+    objects.push(SimpleObject::new(
+        PlaneGeometry::new(Vec3::new(0.0, 0.0, -0.5), Vec3::new(0.0, 0.0, 1.0)),
+        Material::metal(Vec3::new(0.3, 0.4, 0.8), 0.05),
+    ));
+    objects.push(SimpleObject::new(
+        SphereGeometry::new(Vec3::new(-30.0, 20.0, 30.0), 5.0),
+        Material::light(Vec3::new(1.0, 0.9, 0.8)),
+    ));
+
+    let scene = Scene::new(
+        objects,
+        Environment::LinearGradient(Vec3::new(1.0, 1.0, 1.0), Vec3::new(0.5, 0.7, 1.0)),
+    );
+
+    let camera = Camera::look_at(
+        Vec3::new(0.0, -16.0, 7.0),
+        Vec3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, 1.0),
+        50.0,
+    );
+
+    let img = parallel_render(
+        &camera,
+        &scene,
+        &RenderConfig {
+            width: 800,
+            height: 450,
+            max_bounces: 4,
+            samples: 10,
+            direct_lighting: true,
+            soft_shadows: true,
+            filter: Filter::default(),
+            sampling: Sampling::default(),
+        },
+    );
+    img.save("terrain.png").expect("cannot save output image");
+
+    opener::open("terrain.png")
+}
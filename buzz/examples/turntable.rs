@@ -0,0 +1,60 @@
+use std::f64::consts::PI;
+
+use geo::{util::opener, Vec3};
+
+use buzz::{
+    animation::{render_sequence, CameraAnimation, CameraKeyframe, Interpolation},
+    *,
+};
+
+pub fn main() -> opener::Result<()> {
+    let mut objects = SceneObjects::new();
+    objects.push(SimpleObject::new(
+        PlaneGeometry::new(Vec3::zero(), Vec3::new(0.0, 0.0, 1.0)),
+        Material::lambertian(Vec3::new(1.0, 1.0, 1.0)),
+    ));
+    objects.push(SimpleObject::new(
+        SphereGeometry::new(Vec3::new(0.0, 0.0, 1.0), 1.0),
+        Material::lambertian(Vec3::new(0.8, 0.3, 0.3)),
+    ));
+    objects.push(SimpleObject::new(
+        SphereGeometry::new(Vec3::new(0.0, 0.0, 5.0), 1.0),
+        Material::light(Vec3::new(0.5, 0.5, 0.5)),
+    ));
+
+    let scene = Scene::new(objects, Environment::Color(Vec3::zero()));
+
+    let mut animation = CameraAnimation::new(Vec3::new(0.0, 0.0, 1.0), Interpolation::CatmullRom);
+    for i in 0..=8 {
+        let a = f64::from(i) / 8.0 * 2.0 * PI;
+
+        animation.add_keyframe(CameraKeyframe {
+            time: f64::from(i),
+            position: Vec3::new(a.cos() * 4.0, a.sin() * 4.0, 3.0),
+            target: Vec3::new(0.0, 0.0, 0.5),
+            fovy: 50.0,
+            focus: None,
+        });
+    }
+
+    std::fs::create_dir_all("turntable")?;
+    render_sequence(
+        &animation,
+        &scene,
+        &RenderConfig {
+            width: 400,
+            height: 300,
+            max_bounces: 5,
+            samples: 10,
+            direct_lighting: true,
+            soft_shadows: true,
+            filter: Filter::default(),
+            sampling: Sampling::default(),
+        },
+        48,
+        "turntable",
+    )
+    .expect("cannot save output images");
+
+    opener::open("turntable")
+}
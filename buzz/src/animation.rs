@@ -0,0 +1,359 @@
+//! Simple keyframe based camera animation.
+
+use std::path::{Path, PathBuf};
+
+use geo::Vec3;
+
+use crate::{parallel_render, Camera, RenderConfig, Scene};
+
+/// The state of a `Camera` at a given instant in time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CameraKeyframe {
+    /// the instant in time of this keyframe.
+    pub time: f64,
+
+    /// where the camera is positioned.
+    pub position: Vec3,
+
+    /// the point the camera looks at.
+    pub target: Vec3,
+
+    /// vertical field of view in degrees.
+    pub fovy: f64,
+
+    /// optional focal point and aperture radius, see `Camera::with_focus`.
+    pub focus: Option<(Vec3, f64)>,
+}
+
+/// How to interpolate the values between two keyframes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interpolation {
+    /// Interpolate linearly between each pair of keyframes.
+    Linear,
+
+    /// Interpolate smoothly using a [Catmull-Rom spline][0] passing through
+    /// all the keyframes.
+    ///
+    /// [0]: https://en.wikipedia.org/wiki/Cubic_Hermite_spline#Catmull%E2%80%93Rom_spline
+    CatmullRom,
+}
+
+/// A `CameraAnimation` is a sequence of keyframes that describes how a
+/// `Camera` moves over time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CameraAnimation {
+    keyframes: Vec<CameraKeyframe>,
+    vup: Vec3,
+    interpolation: Interpolation,
+}
+
+impl CameraAnimation {
+    /// Create a new empty `CameraAnimation` where the cameras are oriented with
+    /// the given `vup` axis.
+    pub fn new(vup: Vec3, interpolation: Interpolation) -> Self {
+        CameraAnimation {
+            keyframes: vec![],
+            vup,
+            interpolation,
+        }
+    }
+
+    /// Add a new keyframe to the animation. Keyframes can be added in any
+    /// order, but their time must be finite.
+    pub fn add_keyframe(&mut self, keyframe: CameraKeyframe) {
+        assert!(
+            keyframe.time.is_finite(),
+            "keyframe time must be finite, got {}",
+            keyframe.time
+        );
+
+        let i = self
+            .keyframes
+            .iter()
+            .position(|k| k.time > keyframe.time)
+            .unwrap_or(self.keyframes.len());
+
+        self.keyframes.insert(i, keyframe);
+    }
+
+    /// Consume the `CameraAnimation` and return a new one with the given
+    /// keyframe.
+    pub fn with_keyframe(mut self, keyframe: CameraKeyframe) -> Self {
+        self.add_keyframe(keyframe);
+        self
+    }
+
+    /// Return the time range covered by the keyframes, if any.
+    pub fn time_range(&self) -> Option<(f64, f64)> {
+        let first = self.keyframes.first()?;
+        let last = self.keyframes.last()?;
+
+        Some((first.time, last.time))
+    }
+
+    /// Calculate the interpolated keyframe at the given time. Times outside
+    /// the range of the keyframes are clamped. Return `None` if there are no
+    /// keyframes.
+    pub fn keyframe_at(&self, time: f64) -> Option<CameraKeyframe> {
+        let (start, end) = self.time_range()?;
+        let time = time.clamp(start, end);
+
+        let i = self
+            .keyframes
+            .iter()
+            .rposition(|k| k.time <= time)
+            .unwrap_or(0)
+            .min(self.keyframes.len().saturating_sub(2));
+
+        let k1 = &self.keyframes[i];
+        let k2 = match self.keyframes.get(i + 1) {
+            Some(k) => k,
+            None => return Some(k1.clone()),
+        };
+
+        let k0 = &self.keyframes[i.saturating_sub(1)];
+        let k3 = self.keyframes.get(i + 2).unwrap_or(k2);
+
+        let t = if k2.time > k1.time {
+            (time - k1.time) / (k2.time - k1.time)
+        } else {
+            0.0
+        };
+
+        let vec = |f: fn(&CameraKeyframe) -> Vec3| match self.interpolation {
+            Interpolation::Linear => Vec3::lerp(f(k1), f(k2), t),
+            Interpolation::CatmullRom => Vec3::new(
+                catmull_rom(f(k0).x, f(k1).x, f(k2).x, f(k3).x, t),
+                catmull_rom(f(k0).y, f(k1).y, f(k2).y, f(k3).y, t),
+                catmull_rom(f(k0).z, f(k1).z, f(k2).z, f(k3).z, t),
+            ),
+        };
+
+        let fovy = match self.interpolation {
+            Interpolation::Linear => k1.fovy * (1.0 - t) + k2.fovy * t,
+            Interpolation::CatmullRom => catmull_rom(k0.fovy, k1.fovy, k2.fovy, k3.fovy, t),
+        };
+
+        // focus is interpolated only when both keyframes have one, otherwise
+        // the focus of the previous keyframe is kept
+        let focus = match (&k1.focus, &k2.focus) {
+            (Some((p1, r1)), Some((p2, r2))) => {
+                Some((Vec3::lerp(*p1, *p2, t), r1 * (1.0 - t) + r2 * t))
+            }
+            (focus, _) => *focus,
+        };
+
+        Some(CameraKeyframe {
+            time,
+            position: vec(|k| k.position),
+            target: vec(|k| k.target),
+            fovy,
+            focus,
+        })
+    }
+
+    /// Create the `Camera` at the given time. Return `None` if there are no
+    /// keyframes.
+    pub fn camera_at(&self, time: f64) -> Option<Camera> {
+        let k = self.keyframe_at(time)?;
+
+        let camera = Camera::look_at(k.position, k.target, self.vup, k.fovy);
+
+        Some(match k.focus {
+            Some((focal_point, aperture_radius)) => camera.with_focus(focal_point, aperture_radius),
+            None => camera,
+        })
+    }
+}
+
+/// Render `frames` evenly spaced frames of a `CameraAnimation` saving them as
+/// numbered PNG images inside `dir`. The same `Scene` is reused for all the
+/// frames. Return the paths of the saved images.
+pub fn render_sequence(
+    animation: &CameraAnimation,
+    scene: &Scene,
+    config: &RenderConfig,
+    frames: u32,
+    dir: impl AsRef<Path>,
+) -> image::ImageResult<Vec<PathBuf>> {
+    let mut paths = Vec::with_capacity(frames as usize);
+
+    for (frame, time) in frame_times(animation, frames).into_iter().enumerate() {
+        let camera = animation.camera_at(time).unwrap();
+        let img = parallel_render(&camera, scene, config);
+
+        let path = dir.as_ref().join(format!("{:05}.png", frame));
+        img.save(&path)?;
+        paths.push(path);
+    }
+
+    Ok(paths)
+}
+
+/// Return the times of `frames` evenly spaced frames going from the first to
+/// the last keyframe of the animation.
+fn frame_times(animation: &CameraAnimation, frames: u32) -> Vec<f64> {
+    let (start, end) = match animation.time_range() {
+        Some(r) => r,
+        None => return vec![],
+    };
+
+    (0..frames)
+        .map(|frame| {
+            let t = if frames > 1 {
+                f64::from(frame) / f64::from(frames - 1)
+            } else {
+                0.0
+            };
+
+            start + (end - start) * t
+        })
+        .collect()
+}
+
+/// Uniform Catmull-Rom interpolation between `p1` and `p2` using `p0` and `p3`
+/// as control points.
+fn catmull_rom(p0: f64, p1: f64, p2: f64, p3: f64, t: f64) -> f64 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+
+    0.5 * ((2.0 * p1)
+        + (-p0 + p2) * t
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+        + (-p0 + 3.0 * p1 - 3.0 * p2 + p3) * t3)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        Environment, Filter, Material, Sampling, SceneObjects, SimpleObject, SphereGeometry,
+    };
+
+    fn keyframe(time: f64, position: Vec3) -> CameraKeyframe {
+        CameraKeyframe {
+            time,
+            position,
+            target: Vec3::zero(),
+            fovy: 50.0,
+            focus: None,
+        }
+    }
+
+    #[test]
+    fn test_keyframe_at() {
+        let vup = Vec3::new(0.0, 0.0, 1.0);
+
+        let anim = CameraAnimation::new(vup, Interpolation::Linear)
+            .with_keyframe(keyframe(1.0, Vec3::new(10.0, 0.0, 0.0)))
+            .with_keyframe(keyframe(0.0, Vec3::new(0.0, 0.0, 0.0)))
+            .with_keyframe(keyframe(2.0, Vec3::new(10.0, 10.0, 0.0)));
+
+        assert_eq!(anim.time_range(), Some((0.0, 2.0)));
+        assert_eq!(
+            anim.keyframe_at(0.5).unwrap().position,
+            Vec3::new(5.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            anim.keyframe_at(1.5).unwrap().position,
+            Vec3::new(10.0, 5.0, 0.0)
+        );
+        assert_eq!(
+            anim.keyframe_at(5.0).unwrap().position,
+            Vec3::new(10.0, 10.0, 0.0)
+        );
+
+        // splines must pass through the keyframes
+        let anim = CameraAnimation {
+            interpolation: Interpolation::CatmullRom,
+            ..anim
+        };
+        assert_eq!(anim.keyframe_at(0.0).unwrap().position, Vec3::zero());
+        assert_eq!(
+            anim.keyframe_at(1.0).unwrap().position,
+            Vec3::new(10.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            anim.keyframe_at(2.0).unwrap().position,
+            Vec3::new(10.0, 10.0, 0.0)
+        );
+
+        assert_eq!(
+            CameraAnimation::new(vup, Interpolation::Linear).keyframe_at(0.0),
+            None
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_nan_keyframe() {
+        CameraAnimation::new(Vec3::new(0.0, 0.0, 1.0), Interpolation::Linear)
+            .with_keyframe(keyframe(f64::NAN, Vec3::zero()));
+    }
+
+    #[test]
+    fn test_render_sequence() {
+        let mut objects = SceneObjects::new();
+        objects.push(SimpleObject::new(
+            SphereGeometry::new(Vec3::zero(), 1.0),
+            Material::light(Vec3::replicate(1.0)),
+        ));
+        let scene = Scene::new(objects, Environment::Color(Vec3::zero()));
+
+        // the camera slides sideways away from the light
+        let frame = |time, y| CameraKeyframe {
+            target: Vec3::new(0.0, y, 0.0),
+            fovy: 5.0,
+            ..keyframe(time, Vec3::new(-5.0, y, 0.0))
+        };
+        let anim = CameraAnimation::new(Vec3::new(0.0, 0.0, 1.0), Interpolation::Linear)
+            .with_keyframe(frame(1.0, 0.0))
+            .with_keyframe(frame(3.0, 2.0));
+
+        assert_eq!(frame_times(&anim, 3), vec![1.0, 2.0, 3.0]);
+        assert_eq!(frame_times(&anim, 1), vec![1.0]);
+        assert!(frame_times(
+            &CameraAnimation::new(Vec3::new(0.0, 0.0, 1.0), Interpolation::Linear),
+            3
+        )
+        .is_empty());
+
+        let config = RenderConfig {
+            width: 4,
+            height: 4,
+            max_bounces: 1,
+            samples: 1,
+            direct_lighting: false,
+            soft_shadows: false,
+            filter: Filter::default(),
+            sampling: Sampling::default(),
+        };
+
+        let dir = std::env::temp_dir().join(format!("buzz-sequence-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let paths = render_sequence(&anim, &scene, &config, 3, &dir).unwrap();
+        let frames = paths
+            .iter()
+            .map(|p| image::open(p).unwrap().to_rgb8())
+            .collect::<Vec<_>>();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            paths,
+            vec![
+                dir.join("00000.png"),
+                dir.join("00001.png"),
+                dir.join("00002.png")
+            ]
+        );
+
+        // the light fills the first frame, halfway the camera is right above
+        // its edge and then it's out of sight
+        assert!(frames[0].pixels().all(|p| p.0 == [255, 255, 255]));
+        assert!(frames[1].pixels().any(|p| p.0 == [255, 255, 255]));
+        assert!(frames[1].pixels().any(|p| p.0 == [0, 0, 0]));
+        assert!(frames[2].pixels().all(|p| p.0 == [0, 0, 0]));
+    }
+}
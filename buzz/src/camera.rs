@@ -1,11 +1,15 @@
 #![allow(clippy::many_single_char_names)]
 
-use std::f64::consts::PI;
+use std::{f64::consts::PI, sync::Arc};
 
-use rand::Rng;
+use image::GrayImage;
+use rand::SeedableRng;
+use rand_xorshift::XorShiftRng;
 
 use geo::{ray::Ray, Vec3};
 
+use crate::sampler::Sampler;
+
 /// A `Camera` is an object that allows to cast rays towards a 3D point in world
 /// space that is calculated from a 2D point in screen space.
 #[derive(Debug, Clone, PartialEq)]
@@ -21,7 +25,81 @@ pub struct Camera {
     // fovy factor
     m: f64,
 
+    projection: Projection,
     lens: Option<Lens>,
+    aperture: Aperture,
+
+    // shutter open and close times
+    shutter: Option<(f64, f64)>,
+}
+
+/// The `Projection` used by a `Camera` to map points on the screen to rays.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Projection {
+    /// Pinhole perspective projection where all the rays start from the
+    /// position of the `Camera`.
+    Perspective,
+
+    /// [Orthographic projection][0] where all the rays are parallel to the
+    /// viewing direction. The `height` is the vertical size of the viewport in
+    /// world units while the horizontal one depends on the aspect ratio.
+    ///
+    /// [0]: https://en.wikipedia.org/wiki/Orthographic_projection
+    Orthographic { height: f64 },
+
+    /// [Equirectangular projection][0] covering the full 360°x180° sphere
+    /// around the `Camera`. The horizontal axis of the image maps to the
+    /// longitude and the vertical one to the latitude, therefore images should
+    /// have an aspect ratio of 2:1.
+    ///
+    /// [0]: https://en.wikipedia.org/wiki/Equirectangular_projection
+    Equirectangular,
+
+    /// [Cube map][0] where the six faces of the cube are laid out in a 3x2
+    /// grid. The top row contains the right, left and up faces while the
+    /// bottom row contains the down, front and back faces. Each face has a 90°
+    /// field of view, therefore images should have an aspect ratio of 3:2.
+    ///
+    /// [0]: https://en.wikipedia.org/wiki/Cube_mapping
+    CubeMap,
+
+    /// Equidistant [fisheye][0] projection where the distance from the center
+    /// of the image is proportional to the angle from the viewing direction.
+    /// The `fov` in degrees is the field of view covered by the circle
+    /// inscribed in the image, pixels outside of it keep following the same
+    /// mapping.
+    ///
+    /// [0]: https://en.wikipedia.org/wiki/Fisheye_lens
+    Fisheye { fov: f64 },
+}
+
+/// The shape of the `Camera` aperture that determines the shape of the out of
+/// focus highlights, also known as [bokeh][0].
+///
+/// [0]: https://en.wikipedia.org/wiki/Bokeh
+#[derive(Debug, Clone, PartialEq)]
+pub enum Aperture {
+    /// Circular aperture that produces perfectly round highlights.
+    Disc,
+
+    /// Regular polygon with the given number of `blades` rotated by `rotation`
+    /// radians.
+    Polygon { blades: u32, rotation: f64 },
+
+    /// Custom aperture shape described by a grayscale image, see
+    /// `Aperture::mask`.
+    Mask(Arc<ApertureMask>),
+}
+
+/// The distribution of the points that pass through the image of an
+/// `Aperture::Mask`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApertureMask {
+    width: u32,
+    height: u32,
+
+    // cumulative intensity of the pixels in row-major order
+    cdf: Vec<f64>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -55,10 +133,52 @@ impl Camera {
             w,
             m,
 
+            projection: Projection::Perspective,
             lens: None,
+            aperture: Aperture::Disc,
+            shutter: None,
         }
     }
 
+    /// Create an orthographic `Camera` positioned at `position` pointed
+    /// towards the given `target`. The `height` is the vertical size of the
+    /// viewport in world units and `vup` is the up axis used to orient the
+    /// camera.
+    ///
+    /// Orthographic cameras are useful for technical or isometric renders
+    /// because objects don't get smaller as they get farther from the camera.
+    pub fn orthographic(position: Vec3, target: Vec3, vup: Vec3, height: f64) -> Self {
+        let mut camera = Camera::look_at(position, target, vup, 90.0);
+        camera.projection = Projection::Orthographic { height };
+        camera
+    }
+
+    /// Create a `Camera` at `position` that captures the whole scene around it
+    /// using an equirectangular projection. The center of the image looks
+    /// towards `target` and `vup` is the up axis.
+    pub fn equirectangular(position: Vec3, target: Vec3, vup: Vec3) -> Self {
+        let mut camera = Camera::look_at(position, target, vup, 90.0);
+        camera.projection = Projection::Equirectangular;
+        camera
+    }
+
+    /// Create a `Camera` at `position` that captures the whole scene around it
+    /// as the six faces of a cube map. The front face looks towards `target`
+    /// and `vup` is the up axis.
+    pub fn cube_map(position: Vec3, target: Vec3, vup: Vec3) -> Self {
+        let mut camera = Camera::look_at(position, target, vup, 90.0);
+        camera.projection = Projection::CubeMap;
+        camera
+    }
+
+    /// Create an equidistant fisheye `Camera` at `position` pointed towards
+    /// `target` with the given field of view in degrees. `vup` is the up axis.
+    pub fn fisheye(position: Vec3, target: Vec3, vup: Vec3, fov: f64) -> Self {
+        let mut camera = Camera::look_at(position, target, vup, 90.0);
+        camera.projection = Projection::Fisheye { fov };
+        camera
+    }
+
     /// Change the camera focal point and aperture radius to change the depth of
     /// view of the scene.
     pub fn with_focus(mut self, focal_point: Vec3, aperture_radius: f64) -> Camera {
@@ -70,15 +190,29 @@ impl Camera {
         self
     }
 
+    /// Change the shape of the aperture used when the camera has a focus. By
+    /// default the aperture is a disc.
+    pub fn with_aperture(mut self, aperture: Aperture) -> Camera {
+        self.aperture = aperture;
+        self
+    }
+
+    /// Change the times at which the camera shutter opens and closes. Rays are
+    /// cast at random times in between so that moving objects are blurred.
+    pub fn with_shutter(mut self, open: f64, close: f64) -> Camera {
+        self.shutter = Some((open, close));
+        self
+    }
+
     /// Create a `Ray` that starts from the `Camera`'s position to the 3D space
     /// with a direction that makes it pass through a given 2D point inside the
-    /// viewport. A `Rng` is needed to slightly perturb the generated rays to
-    /// improve the quality of the rendering.
+    /// viewport. A `Sampler` is needed to slightly perturb the generated rays
+    /// to improve the quality of the rendering.
     pub fn cast_ray(
         &self,
         (x, y): (u32, u32),
         (width, height): (u32, u32),
-        rng: &mut impl Rng,
+        sampler: &mut impl Sampler,
     ) -> Ray {
         let x = f64::from(x);
 
@@ -87,38 +221,219 @@ impl Camera {
         // top left and y grows downwards.
         let y = f64::from(height - y);
 
+        let (u, v) = sampler.next_2d();
+
+        self.cast_film_ray((x + u, y + v), (width, height), sampler)
+    }
+
+    /// Create a `Ray` that passes through the given continuous point of the
+    /// image where the pixel `(x, y)` covers the area from `(x, y)` to
+    /// `(x + 1, y + 1)`. Unlike `cast_ray` the point is not perturbed, but a
+    /// `Sampler` is still needed to sample the lens and the shutter.
+    pub fn cast_ray_at(
+        &self,
+        (x, y): (f64, f64),
+        (width, height): (u32, u32),
+        sampler: &mut impl Sampler,
+    ) -> Ray {
+        self.cast_film_ray((x, f64::from(height) - y + 1.0), (width, height), sampler)
+    }
+
+    /// Create the `Ray` that passes through the center of the pixel `(x, y)`
+    /// ignoring both the lens and the shutter. The returned `Ray` is always the
+    /// same for a given pixel which makes it useful to query the `Scene`.
+    pub fn cast_center_ray(&self, (x, y): (u32, u32), (width, height): (u32, u32)) -> Ray {
+        let pinhole = Camera {
+            lens: None,
+            shutter: None,
+            ..self.clone()
+        };
+
+        // no random numbers are drawn without lens and shutter
+        let mut rng = XorShiftRng::seed_from_u64(0);
+
+        pinhole.cast_ray_at(
+            (f64::from(x) + 0.5, f64::from(y) + 0.5),
+            (width, height),
+            &mut rng,
+        )
+    }
+
+    /// Cast a `Ray` through the given point of the film where the y axis grows
+    /// upwards.
+    fn cast_film_ray(
+        &self,
+        (sx, sy): (f64, f64),
+        (width, height): (u32, u32),
+        sampler: &mut impl Sampler,
+    ) -> Ray {
         let width = f64::from(width);
         let height = f64::from(height);
 
-        let u: f64 = rng.gen();
-        let v: f64 = rng.gen();
-
         let aspect = width / height;
-        let ndcx = (x + u - 0.5) / (width - 1.0) * 2.0 - 1.0;
-        let ndcy = (y + v - 0.5) / (height - 1.0) * 2.0 - 1.0;
+        let ndcx = (sx - 0.5) / (width - 1.0) * 2.0 - 1.0;
+        let ndcy = (sy - 0.5) / (height - 1.0) * 2.0 - 1.0;
+
+        let (ro, rd) = match self.projection {
+            Projection::Perspective => {
+                let mut rd = Vec3::zero();
+                rd += self.u * ndcx * aspect;
+                rd += self.v * ndcy;
+                rd += self.w * self.m;
+                rd.normalize();
+
+                (self.position, rd)
+            }
+            Projection::Orthographic { height } => {
+                let mut ro = self.position;
+                ro += self.u * (ndcx * aspect * height / 2.0);
+                ro += self.v * (ndcy * height / 2.0);
+
+                (ro, self.w)
+            }
+            Projection::Equirectangular => {
+                let (sx, sy) = ((sx) / width, (sy - 1.0) / height);
+
+                let phi = (sx * 2.0 - 1.0) * PI;
+                let theta = (sy - 0.5) * PI;
 
-        let mut rd = Vec3::zero();
-        rd += self.u * ndcx * aspect;
-        rd += self.v * ndcy;
-        rd += self.w * self.m;
-        rd.normalize();
+                let mut rd = self.w * (theta.cos() * phi.cos());
+                rd += self.u * (theta.cos() * phi.sin());
+                rd += self.v * theta.sin();
+
+                (self.position, rd)
+            }
+            Projection::CubeMap => {
+                let (sx, sy) = ((sx) / width, (sy - 1.0) / height);
+
+                let col = (sx * 3.0).floor().clamp(0.0, 2.0);
+                let row = ((1.0 - sy) * 2.0).floor().clamp(0.0, 1.0);
+
+                let a = (sx * 3.0 - col) * 2.0 - 1.0;
+                let b = (sy * 2.0 - (1.0 - row)) * 2.0 - 1.0;
+
+                // forward, right and up directions of each face
+                let (f, r, up) = match (row as u8, col as u8) {
+                    (0, 0) => (self.u, -self.w, self.v),
+                    (0, 1) => (-self.u, self.w, self.v),
+                    (0, _) => (self.v, self.u, -self.w),
+                    (_, 0) => (-self.v, self.u, self.w),
+                    (_, 1) => (self.w, self.u, self.v),
+                    (_, _) => (-self.w, -self.u, self.v),
+                };
+
+                (self.position, (f + r * a + up * b).normalized())
+            }
+            Projection::Fisheye { fov } => {
+                let r = width.min(height) / 2.0;
+                let nx = (sx - width / 2.0) / r;
+                let ny = (sy - 1.0 - height / 2.0) / r;
 
-        match self.lens {
+                let theta = (nx.powi(2) + ny.powi(2)).sqrt() * fov.to_radians() / 2.0;
+                let phi = ny.atan2(nx);
+
+                let mut rd = self.w * theta.cos();
+                rd += self.u * (theta.sin() * phi.cos());
+                rd += self.v * (theta.sin() * phi.sin());
+
+                (self.position, rd)
+            }
+        };
+
+        let ray = match self.lens {
             Some(Lens {
                 aperture_radius,
                 focal_distance,
             }) => {
-                let focal_point = self.position + rd * focal_distance;
-                let angle = rng.gen::<f64>() * 2.0 * PI;
-                let radius = rng.gen::<f64>() * aperture_radius;
+                let focal_point = ro + rd * focal_distance;
+                let (ax, ay) = self.aperture.sample(sampler);
 
-                let p = self.position
-                    + self.u * (angle.cos() * radius)
-                    + self.v * (angle.sin() * radius);
+                let p = ro + self.u * (ax * aperture_radius) + self.v * (ay * aperture_radius);
 
                 Ray::new(p, (focal_point - p).normalized())
             }
-            None => Ray::new(self.position, rd),
+            None => Ray::new(ro, rd),
+        };
+
+        match self.shutter {
+            Some((open, close)) => ray.with_time(open + sampler.next_1d() * (close - open)),
+            None => ray,
+        }
+    }
+}
+
+impl Aperture {
+    /// Create a custom aperture shape described by a grayscale image where
+    /// white pixels let light pass through and black pixels block it. The
+    /// image is stretched to cover the square that contains the aperture
+    /// circle. Return `None` if the image is empty or completely black since
+    /// no light would pass.
+    pub fn mask(image: &GrayImage) -> Option<Self> {
+        let mut total = 0.0;
+        let cdf = image
+            .pixels()
+            .map(|image::Luma([l])| {
+                total += f64::from(*l);
+                total
+            })
+            .collect::<Vec<_>>();
+
+        if total <= 0.0 {
+            return None;
+        }
+
+        Some(Aperture::Mask(Arc::new(ApertureMask {
+            width: image.width(),
+            height: image.height(),
+            cdf,
+        })))
+    }
+
+    /// Sample a random point of the aperture scaled so that it fits in the
+    /// unit circle, except for masks whose image is stretched over the whole
+    /// [-1, 1] square.
+    fn sample(&self, sampler: &mut impl Sampler) -> (f64, f64) {
+        match self {
+            Aperture::Disc => {
+                let (a, radius) = sampler.next_2d();
+                let angle = a * 2.0 * PI;
+
+                (angle.cos() * radius, angle.sin() * radius)
+            }
+            Aperture::Polygon { blades, rotation } => {
+                let blades = (*blades).max(3);
+
+                // pick one of the triangles between the center and two
+                // consecutive vertices and sample a point uniformly inside it
+                let i = ((sampler.next_1d() * f64::from(blades)) as u32).min(blades - 1);
+                let a0 = rotation + 2.0 * PI * f64::from(i) / f64::from(blades);
+                let a1 = rotation + 2.0 * PI * f64::from(i + 1) / f64::from(blades);
+
+                let (mut r0, mut r1) = sampler.next_2d();
+                if r0 + r1 > 1.0 {
+                    r0 = 1.0 - r0;
+                    r1 = 1.0 - r1;
+                }
+
+                (a0.cos() * r0 + a1.cos() * r1, a0.sin() * r0 + a1.sin() * r1)
+            }
+            Aperture::Mask(mask) => {
+                // pick a pixel proportionally to its intensity and then a
+                // uniform point inside it
+                let total = mask.cdf[mask.cdf.len() - 1];
+                let target = sampler.next_1d() * total;
+                let i = mask
+                    .cdf
+                    .partition_point(|&c| c <= target)
+                    .min(mask.cdf.len() - 1) as u32;
+
+                let (w, h) = (f64::from(mask.width), f64::from(mask.height));
+                let (px, py) = (f64::from(i % mask.width), f64::from(i / mask.width));
+                let (u, v) = sampler.next_2d();
+
+                // the rows of the image grow downwards
+                ((px + u) / w * 2.0 - 1.0, 1.0 - (py + v) / h * 2.0)
+            }
         }
     }
 }
@@ -128,7 +443,7 @@ mod tests {
     use rand::SeedableRng;
     use rand_xorshift::XorShiftRng;
 
-    use super::{Camera, Ray, Vec3};
+    use super::{Aperture, Camera, GrayImage, Ray, Vec3};
 
     #[test]
     fn test_look_at() {
@@ -184,6 +499,71 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_cast_ray_orthographic() {
+        let mut rng = XorShiftRng::seed_from_u64(0);
+
+        let c = Camera::orthographic(
+            Vec3::new(0.0, 0.0, 5.0),
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            2.0,
+        );
+
+        let r = c.cast_ray((200, 100), (400, 200), &mut rng);
+        assert_eq!(r.dir, Vec3::new(0.0, 0.0, -1.0));
+        assert!(r.origin.x.abs() < 1e-2);
+        assert!(r.origin.y.abs() < 1e-2);
+        assert_eq!(r.origin.z, 5.0);
+
+        let r = c.cast_ray((0, 0), (400, 200), &mut rng);
+        assert_eq!(r.dir, Vec3::new(0.0, 0.0, -1.0));
+        assert!((r.origin.x + 2.0).abs() < 2e-2);
+        assert!((r.origin.y - 1.0).abs() < 2e-2);
+        assert_eq!(r.origin.z, 5.0);
+    }
+
+    #[test]
+    fn test_cast_ray_panoramic() {
+        let mut rng = XorShiftRng::seed_from_u64(0);
+
+        let position = Vec3::new(0.0, 0.0, 5.0);
+        let target = Vec3::zero();
+        let vup = Vec3::new(0.0, 1.0, 0.0);
+        let forward = Vec3::new(0.0, 0.0, -1.0);
+
+        let close_to = |a: Vec3, b: Vec3| a.normalized().dist(b) < 5e-2;
+
+        let c = Camera::equirectangular(position, target, vup);
+        let r = c.cast_ray((200, 100), (400, 200), &mut rng);
+        assert_eq!(r.origin, position);
+        assert!(close_to(r.dir, forward));
+        let r = c.cast_ray((0, 100), (400, 200), &mut rng);
+        assert!(close_to(r.dir, -forward));
+        let r = c.cast_ray((300, 100), (400, 200), &mut rng);
+        assert!(close_to(r.dir, Vec3::new(1.0, 0.0, 0.0)));
+        let r = c.cast_ray((200, 0), (400, 200), &mut rng);
+        assert!(close_to(r.dir, vup));
+
+        let c = Camera::cube_map(position, target, vup);
+        let r = c.cast_ray((300, 300), (600, 400), &mut rng);
+        assert!(close_to(r.dir, forward));
+        let r = c.cast_ray((500, 300), (600, 400), &mut rng);
+        assert!(close_to(r.dir, -forward));
+        let r = c.cast_ray((100, 100), (600, 400), &mut rng);
+        assert!(close_to(r.dir, Vec3::new(1.0, 0.0, 0.0)));
+        let r = c.cast_ray((500, 100), (600, 400), &mut rng);
+        assert!(close_to(r.dir, vup));
+
+        let c = Camera::fisheye(position, target, vup, 180.0);
+        let r = c.cast_ray((200, 200), (400, 400), &mut rng);
+        assert!(close_to(r.dir, forward));
+        let r = c.cast_ray((399, 200), (400, 400), &mut rng);
+        assert!(close_to(r.dir, Vec3::new(1.0, 0.0, 0.0)));
+        let r = c.cast_ray((200, 0), (400, 400), &mut rng);
+        assert!(close_to(r.dir, vup));
+    }
+
     #[test]
     fn test_cast_ray_with_focus() {
         let mut rng = XorShiftRng::seed_from_u64(0);
@@ -208,4 +588,74 @@ mod tests {
             )
         );
     }
+
+    #[test]
+    fn test_cast_ray_with_shutter() {
+        let mut rng = XorShiftRng::seed_from_u64(0);
+
+        let c = Camera::look_at(
+            Vec3::new(0.0, 0.0, 5.0),
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            45.0,
+        );
+        assert_eq!(c.cast_ray((10, 10), (400, 200), &mut rng).time, 0.0);
+
+        let c = c.with_shutter(0.2, 0.6);
+        let times = (0..100)
+            .map(|_| c.cast_ray((10, 10), (400, 200), &mut rng).time)
+            .collect::<Vec<_>>();
+        assert!(times.iter().all(|t| (0.2..0.6).contains(t)));
+        assert!(times.iter().any(|t| *t < 0.3));
+        assert!(times.iter().any(|t| *t > 0.5));
+
+        // the center rays ignore the shutter
+        assert_eq!(c.cast_center_ray((10, 10), (400, 200)).time, 0.0);
+    }
+
+    #[test]
+    fn test_aperture_polygon() {
+        let mut rng = XorShiftRng::seed_from_u64(0);
+
+        // a square with 4 blades and no rotation has its vertices on the axes
+        let aperture = Aperture::Polygon {
+            blades: 4,
+            rotation: 0.0,
+        };
+
+        for _ in 0..1000 {
+            let (x, y) = aperture.sample(&mut rng);
+            assert!(x.abs() + y.abs() <= 1.0 + 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_aperture_mask() {
+        let mut rng = XorShiftRng::seed_from_u64(0);
+
+        assert_eq!(Aperture::mask(&GrayImage::new(0, 0)), None);
+        assert_eq!(Aperture::mask(&GrayImage::new(4, 4)), None);
+
+        // only the top right pixel is open, even if it's quite dark
+        let mut image = GrayImage::new(4, 4);
+        image.put_pixel(3, 0, image::Luma([10]));
+        let aperture = Aperture::mask(&image).unwrap();
+
+        for _ in 0..1000 {
+            let (x, y) = aperture.sample(&mut rng);
+            assert!((0.5..=1.0).contains(&x), "{}", x);
+            assert!((0.5..=1.0).contains(&y), "{}", y);
+        }
+
+        // the brighter half gets three times the samples of the other one
+        let mut image = GrayImage::new(2, 1);
+        image.put_pixel(0, 0, image::Luma([255]));
+        image.put_pixel(1, 0, image::Luma([85]));
+        let aperture = Aperture::mask(&image).unwrap();
+
+        let left = (0..4000)
+            .filter(|_| aperture.sample(&mut rng).0 < 0.0)
+            .count();
+        assert!((2800..3200).contains(&left), "{}", left);
+    }
 }
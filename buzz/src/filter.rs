@@ -0,0 +1,190 @@
+//! Pixel [reconstruction filters][0] used to combine the samples taken around
+//! a pixel into its final color.
+//!
+//! [0]: https://www.pbr-book.org/3ed-2018/Sampling_and_Reconstruction/Image_Reconstruction
+
+use std::f64::consts::PI;
+
+/// Enum over all the supported reconstruction `Filter`s. Each sample
+/// contributes to all the pixels whose center is within `radius` pixels from
+/// it, weighted by the filter.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    /// Simple average of the samples inside the filter area. A radius of 0.5
+    /// only averages the samples inside each pixel.
+    Box { radius: f64 },
+
+    /// Triangle filter whose weight linearly falls off from the center of the
+    /// pixel.
+    Tent { radius: f64 },
+
+    /// Gaussian filter with falloff `alpha` shifted so that it reaches 0 at
+    /// `radius`.
+    Gaussian { radius: f64, alpha: f64 },
+
+    /// [Mitchell-Netravali][0] cubic filter parametrized by `b` and `c`. The
+    /// recommended values are `b = c = 1/3`.
+    ///
+    /// [0]: https://en.wikipedia.org/wiki/Mitchell%E2%80%93Netravali_filters
+    MitchellNetravali { radius: f64, b: f64, c: f64 },
+
+    /// [Lanczos][0] windowed sinc filter where `tau` is the number of cycles
+    /// of the window.
+    ///
+    /// [0]: https://en.wikipedia.org/wiki/Lanczos_resampling
+    Lanczos { radius: f64, tau: f64 },
+}
+
+impl Filter {
+    /// The radius in pixels of the filter.
+    pub fn radius(&self) -> f64 {
+        match *self {
+            Filter::Box { radius }
+            | Filter::Tent { radius }
+            | Filter::Gaussian { radius, .. }
+            | Filter::MitchellNetravali { radius, .. }
+            | Filter::Lanczos { radius, .. } => radius,
+        }
+    }
+
+    /// Calculate the weight of a sample at the given offset from the center of
+    /// a pixel. Note that some filters have negative lobes.
+    pub fn weight(&self, dx: f64, dy: f64) -> f64 {
+        self.weight_1d(dx) * self.weight_1d(dy)
+    }
+
+    fn weight_1d(&self, x: f64) -> f64 {
+        match *self {
+            // the range is half open so that a sample never contributes to two
+            // adjacent pixels when the radius is 0.5
+            Filter::Box { radius } => {
+                if x >= -radius && x < radius {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Filter::Tent { radius } => (radius - x.abs()).max(0.0),
+            Filter::Gaussian { radius, alpha } => {
+                ((-alpha * x.powi(2)).exp() - (-alpha * radius.powi(2)).exp()).max(0.0)
+            }
+            Filter::MitchellNetravali { radius, b, c } => {
+                let x = (2.0 * x / radius).abs();
+
+                if x > 2.0 {
+                    0.0
+                } else if x > 1.0 {
+                    ((-b - 6.0 * c) * x.powi(3)
+                        + (6.0 * b + 30.0 * c) * x.powi(2)
+                        + (-12.0 * b - 48.0 * c) * x
+                        + (8.0 * b + 24.0 * c))
+                        / 6.0
+                } else {
+                    ((12.0 - 9.0 * b - 6.0 * c) * x.powi(3)
+                        + (-18.0 + 12.0 * b + 6.0 * c) * x.powi(2)
+                        + (6.0 - 2.0 * b))
+                        / 6.0
+                }
+            }
+            Filter::Lanczos { radius, tau } => {
+                if x.abs() > radius {
+                    0.0
+                } else {
+                    sinc(x) * sinc(x / tau)
+                }
+            }
+        }
+    }
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Filter::Box { radius: 0.5 }
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x.abs() < 1e-5 {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filters() -> Vec<Filter> {
+        vec![
+            Filter::default(),
+            Filter::Tent { radius: 1.5 },
+            Filter::Gaussian {
+                radius: 2.0,
+                alpha: 2.0,
+            },
+            Filter::MitchellNetravali {
+                radius: 2.0,
+                b: 1.0 / 3.0,
+                c: 1.0 / 3.0,
+            },
+            Filter::Lanczos {
+                radius: 3.0,
+                tau: 3.0,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_support() {
+        for f in filters() {
+            let r = f.radius();
+
+            assert!(f.weight(0.0, 0.0) > 0.0, "{:?}", f);
+            assert!(f.weight(0.0, 0.0) >= f.weight(r * 0.5, 0.0), "{:?}", f);
+
+            for d in [r + 1e-6, r + 0.5, 10.0] {
+                assert_eq!(f.weight(d, 0.0), 0.0, "{:?}", f);
+                assert_eq!(f.weight(0.0, -d), 0.0, "{:?}", f);
+                assert_eq!(f.weight(-d, d), 0.0, "{:?}", f);
+            }
+        }
+    }
+
+    #[test]
+    fn test_weights() {
+        // a box of radius 0.5 covers exactly one pixel
+        let b = Filter::default();
+        assert_eq!(b.weight(-0.5, 0.2), 1.0);
+        assert_eq!(b.weight(0.5, 0.2), 0.0);
+        assert_eq!(b.weight(0.49, -0.49), 1.0);
+
+        let t = Filter::Tent { radius: 1.5 };
+        assert!((t.weight(0.5, 0.0) - 1.5).abs() < 1e-9);
+        assert!((t.weight(0.5, 1.0) - 0.5).abs() < 1e-9);
+
+        // the filters are separable and symmetric
+        for f in filters().into_iter().skip(1) {
+            for (dx, dy) in [(0.3, 0.7), (1.2, -0.4), (-0.9, 1.1)] {
+                let w = f.weight(dx, dy);
+                assert!((w - f.weight(-dx, dy)).abs() < 1e-12, "{:?}", f);
+                assert!((w - f.weight(dy, dx)).abs() < 1e-12, "{:?}", f);
+                assert!(
+                    (w - f.weight(dx, 0.0) * f.weight(0.0, dy) / f.weight(0.0, 0.0)).abs() < 1e-12,
+                    "{:?}",
+                    f
+                );
+            }
+        }
+
+        // the Mitchell-Netravali filter has negative lobes far from the center
+        let m = &filters()[3];
+        assert!((m.weight(0.0, 0.0) - (8.0 / 9.0_f64).powi(2)).abs() < 1e-9);
+        assert!(m.weight(1.5, 0.0) < 0.0);
+
+        // the Lanczos filter goes through zero at each integer offset
+        let l = &filters()[4];
+        assert!(l.weight(1.0, 0.0).abs() < 1e-12);
+        assert!(l.weight(0.0, 2.0).abs() < 1e-12);
+    }
+}
@@ -1,9 +1,13 @@
 #![allow(clippy::useless_let_if_seq)]
 
+pub mod animation;
 pub mod camera;
+pub mod filter;
 pub mod material;
 pub mod object;
 pub mod objectgeo;
+pub mod picking;
+pub mod sampler;
 
 mod renderer;
 
@@ -16,10 +20,13 @@ use geo::{
 };
 
 pub use camera::Camera;
+pub use filter::Filter;
 pub use material::Material;
 pub use object::*;
 pub use objectgeo::*;
+pub use picking::{pick, pick_rect, Pick};
 pub use renderer::*;
+pub use sampler::{Sampler, Sampling};
 
 /// A `Scene` is a collection of objects that can be rendered.
 #[derive(Debug)]
@@ -27,6 +34,9 @@ pub struct Scene {
     objects: SceneObjects,
     objects_index: Bvh<Arc<dyn Object>>,
     environment: Environment,
+
+    // the ids of the objects that are lights
+    lights: Vec<usize>,
 }
 
 #[derive(Debug)]
@@ -50,10 +60,17 @@ impl Scene {
     /// `Environment`.
     pub fn new(objects: SceneObjects, environment: Environment) -> Self {
         let objects_index: Bvh<_> = objects.iter().cloned().collect();
+        let lights = objects
+            .iter()
+            .filter(|o| matches!(o.material(), Material::Light { .. }))
+            .map(|o| o.surface_id())
+            .collect();
+
         Scene {
             objects,
             objects_index,
             environment,
+            lights,
         }
     }
 
@@ -67,23 +84,51 @@ impl Scene {
             .map(|(s, t)| (s.as_ref(), t))
     }
 
+    /// Calculate the closest intersection between a `Ray` and the objects in
+    /// the scene that are visible to the given kind of ray.
+    pub fn visible_intersection(&self, ray: &Ray, kind: RayKind) -> Option<(&dyn Object, Hit)> {
+        self.objects_index
+            .intersections(ray)
+            .filter(|(s, _)| s.visibility().is_visible_to(kind))
+            .min_by(|(_, t0), (_, t1)| t0.t().partial_cmp(&t1.t()).unwrap())
+            .map(|(s, t)| (s.as_ref(), t))
+    }
+
+    /// Calculate the closest intersection between a shadow `Ray` cast towards
+    /// the light with the given id and the objects in the scene. Objects that
+    /// do not cast shadows are ignored, but the light itself is always
+    /// reachable.
+    pub fn shadow_intersection(&self, ray: &Ray, light_id: usize) -> Option<(&dyn Object, Hit)> {
+        self.objects_index
+            .intersections(ray)
+            .filter(|(s, _)| {
+                s.visibility().is_visible_to(RayKind::Shadow) || s.surface_id() == light_id
+            })
+            .min_by(|(_, t0), (_, t1)| t0.t().partial_cmp(&t1.t()).unwrap())
+            .map(|(s, t)| (s.as_ref(), t))
+    }
+
     /// Get the `Surface` with the given id.
     pub fn surface(&self, id: usize) -> &dyn Object {
         self.objects[id].as_ref()
     }
 
-    /// Return an iterator over all the lights in the `Scene`.
+    /// Return an iterator over all the lights in the `Scene`, that is the
+    /// objects whose own `Material` is a light. Parts of a surface made of a
+    /// light `Material`, like CSG shapes with a light set by `with_material`,
+    /// glow when hit but are not sampled for direct lighting.
     pub fn lights(&self) -> impl Iterator<Item = &dyn Object> {
-        self.objects
-            .iter()
-            .filter(|o| {
-                if let Material::Light { .. } = o.material() {
-                    true
-                } else {
-                    false
-                }
-            })
-            .map(|o| o.as_ref())
+        self.lights.iter().map(move |&id| self.surface(id))
+    }
+
+    /// Return an iterator over all the lights in the `Scene` that illuminate
+    /// the given `Object` according to its light links.
+    pub fn lights_for<'s>(
+        &'s self,
+        object: &'s dyn Object,
+    ) -> impl Iterator<Item = &'s dyn Object> {
+        self.lights()
+            .filter(move |l| object.is_lit_by(l.surface_id()))
     }
 }
 
@@ -109,3 +154,106 @@ impl std::ops::Index<usize> for SceneObjects {
         &self.objects[index]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_visibility() {
+        let hidden = Visibility {
+            camera: false,
+            shadow: false,
+            reflection: true,
+        };
+
+        let mut objects = SceneObjects::new();
+        objects.push(
+            SimpleObject::new(
+                SphereGeometry::new(Vec3::new(5.0, 0.0, 0.0), 1.0),
+                Material::lambertian(Vec3::new(1.0, 0.0, 0.0)),
+            )
+            .with_visibility(hidden),
+        );
+        objects.push(SimpleObject::new(
+            SphereGeometry::new(Vec3::new(10.0, 0.0, 0.0), 1.0),
+            Material::lambertian(Vec3::new(0.0, 1.0, 0.0)),
+        ));
+        objects.push(
+            SimpleObject::new(
+                SphereGeometry::new(Vec3::new(0.0, 0.0, 10.0), 1.0),
+                Material::light(Vec3::new(1.0, 1.0, 1.0)),
+            )
+            .with_visibility(hidden),
+        );
+        let scene = Scene::new(objects, Environment::Color(Vec3::zero()));
+
+        let ray = Ray::new(Vec3::zero(), Vec3::new(1.0, 0.0, 0.0));
+        let (o, _) = scene.intersection(&ray).unwrap();
+        assert_eq!(o.surface_id(), 0);
+
+        // the camera sees through the first sphere, the reflections don't
+        let (o, _) = scene.visible_intersection(&ray, RayKind::Camera).unwrap();
+        assert_eq!(o.surface_id(), 1);
+        let (o, _) = scene
+            .visible_intersection(&ray, RayKind::Reflection)
+            .unwrap();
+        assert_eq!(o.surface_id(), 0);
+
+        // the first sphere doesn't cast shadows, the second one does
+        let (o, _) = scene.shadow_intersection(&ray, 2).unwrap();
+        assert_eq!(o.surface_id(), 1);
+
+        // the light is reachable even if it's invisible to shadow rays, but only
+        // when it's the one being sampled
+        let ray = Ray::new(Vec3::zero(), Vec3::new(0.0, 0.0, 1.0));
+        let (o, _) = scene.shadow_intersection(&ray, 2).unwrap();
+        assert_eq!(o.surface_id(), 2);
+        assert!(scene.shadow_intersection(&ray, 1).is_none());
+
+        assert_eq!(
+            scene.lights().map(|l| l.surface_id()).collect::<Vec<_>>(),
+            vec![2]
+        );
+    }
+
+    #[test]
+    fn test_lights_for() {
+        let mut objects = SceneObjects::new();
+        for z in [10.0, 20.0] {
+            objects.push(SimpleObject::new(
+                SphereGeometry::new(Vec3::new(0.0, 0.0, z), 1.0),
+                Material::light(Vec3::new(1.0, 1.0, 1.0)),
+            ));
+        }
+        objects.push(SimpleObject::new(
+            SphereGeometry::new(Vec3::zero(), 1.0),
+            Material::lambertian(Vec3::new(1.0, 0.0, 0.0)),
+        ));
+        objects.push(
+            SimpleObject::new(
+                SphereGeometry::new(Vec3::new(5.0, 0.0, 0.0), 1.0),
+                Material::lambertian(Vec3::new(0.0, 1.0, 0.0)),
+            )
+            .with_light_links(LightLinks::Except(vec![0])),
+        );
+        objects.push(
+            SimpleObject::new(
+                SphereGeometry::new(Vec3::new(-5.0, 0.0, 0.0), 1.0),
+                Material::lambertian(Vec3::new(0.0, 0.0, 1.0)),
+            )
+            .with_light_links(LightLinks::Only(vec![])),
+        );
+        let scene = Scene::new(objects, Environment::Color(Vec3::zero()));
+
+        let lights_for = |id| {
+            scene
+                .lights_for(scene.surface(id))
+                .map(|l| l.surface_id())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(lights_for(2), vec![0, 1]);
+        assert_eq!(lights_for(3), vec![1]);
+        assert!(lights_for(4).is_empty());
+    }
+}
@@ -1,7 +1,9 @@
-use rand::Rng;
+use std::f64::consts::PI;
 
 use geo::{ray::Ray, Vec3};
 
+use crate::sampler::Sampler;
+
 /// Enum over all the supported `Material`s. Each variant dictates how light
 /// interacts(reflects, refracts, etc..) with them. They're mainly composed of
 /// an `albedo` field which is the intrinsic color of the material.
@@ -11,6 +13,7 @@ pub enum Material {
     Metal { albedo: Vec3, fuzziness: f64 },
     Dielectric { refraction_index: f64 },
     Light { emittance: Vec3 },
+    OrbitTrap { near: Vec3, far: Vec3 },
 }
 
 impl Material {
@@ -43,15 +46,32 @@ impl Material {
     pub const fn light(emittance: Vec3) -> Self {
         Material::Light { emittance }
     }
+
+    /// A Lambertian material whose albedo goes from `near` to `far` following
+    /// the orbit trap of the hit, that is `near` where the orbit of the fractal
+    /// gets close to the origin. Surfaces without orbit traps are `far`.
+    pub const fn orbit_trap(near: Vec3, far: Vec3) -> Self {
+        Material::OrbitTrap { near, far }
+    }
+
+    /// The albedo of a Lambertian or `OrbitTrap` material for a hit with the
+    /// given orbit trap.
+    pub fn diffuse_albedo(&self, orbit_trap: Option<f64>) -> Option<Vec3> {
+        match *self {
+            Material::Lambertian { albedo } => Some(albedo),
+            Material::OrbitTrap { near, far } => Some(near.lerp(far, orbit_trap.unwrap_or(1.0))),
+            _ => None,
+        }
+    }
 }
 
 /// Calculate the bouncing of a ray coming to `intersection` on a Lambertian
 /// material.
 ///
 /// To calculate the `Ray` the normal at the `intersection` is required
-/// alongside a RNG to slightly perturb the ray.
-pub fn lambertian_bounce(intersection: Vec3, n: Vec3, rng: &mut impl Rng) -> Ray {
-    Ray::new(intersection, n + Vec3::random_unit(rng))
+/// alongside a `Sampler` to slightly perturb the ray.
+pub fn lambertian_bounce(intersection: Vec3, n: Vec3, sampler: &mut impl Sampler) -> Ray {
+    Ray::new(intersection, n + sample_unit_ball(sampler))
 }
 
 /// Calculate the bouncing of a ray coming to `intersection` on a metallic
@@ -60,17 +80,17 @@ pub fn lambertian_bounce(intersection: Vec3, n: Vec3, rng: &mut impl Rng) -> Ray
 /// To calculate the `Ray` the normal at the `intersection` is required
 /// alongside the `fuzziness` of the metallic material.
 ///
-/// Lastly, a RNG to slightly perturb the ray.
+/// Lastly, a `Sampler` to slightly perturb the ray.
 pub fn metal_bounce(
     ray: &Ray,
     intersection: Vec3,
     n: Vec3,
     fuzziness: f64,
-    rng: &mut impl Rng,
+    sampler: &mut impl Sampler,
 ) -> Ray {
     Ray::new(
         intersection,
-        Ray::new(ray.dir.normalized(), n).reflect() + Vec3::random_unit(rng) * fuzziness,
+        Ray::new(ray.dir.normalized(), n).reflect() + sample_unit_ball(sampler) * fuzziness,
     )
 }
 
@@ -80,13 +100,13 @@ pub fn metal_bounce(
 /// To calculate the `Ray` the normal at the `intersection` is required
 /// alongside the `refraction_index` of the dielectric material.
 ///
-/// Lastly, a RNG to slightly perturb the ray.
+/// Lastly, a `Sampler` to slightly perturb the ray.
 pub fn dielectric_bounce(
     ray: &Ray,
     intersection: Vec3,
     n: Vec3,
     refraction_index: f64,
-    rng: &mut impl Rng,
+    sampler: &mut impl Sampler,
 ) -> Ray {
     let outward_normal;
     let ref_ix;
@@ -108,7 +128,7 @@ pub fn dielectric_bounce(
         Some(refracted) => {
             let reflect_prob = schlick(cos, ref_ix);
 
-            if rng.gen::<f64>() < reflect_prob {
+            if sampler.next_1d() < reflect_prob {
                 Ray::new(ray.dir, n).reflect()
             } else {
                 refracted
@@ -130,3 +150,19 @@ fn schlick(cos: f64, refraction_index: f64) -> f64 {
 
     r0 + (1.0 - r0) * (1.0 - cos).powi(5)
 }
+
+/// Sample a point uniformly distributed on the surface of the unit sphere.
+fn sample_unit_sphere(sampler: &mut impl Sampler) -> Vec3 {
+    let (u, v) = sampler.next_2d();
+
+    let z = 1.0 - 2.0 * u;
+    let r = (1.0 - z.powi(2)).max(0.0).sqrt();
+    let phi = 2.0 * PI * v;
+
+    Vec3::new(r * phi.cos(), r * phi.sin(), z)
+}
+
+/// Sample a point uniformly distributed inside the unit sphere.
+fn sample_unit_ball(sampler: &mut impl Sampler) -> Vec3 {
+    sample_unit_sphere(sampler) * sampler.next_1d().cbrt()
+}
@@ -1,12 +1,14 @@
 use geo::{ray::Ray, spatial_index::Shape, Aabb, Triangle, Vec3};
 
-use crate::{material::Material, FacetGeometry, Hit, Object, Surface};
+use crate::{material::Material, FacetGeometry, Hit, LightLinks, Object, Surface, Visibility};
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Facet<'a> {
     geom: FacetGeometry,
     material: &'a Material,
     surface_id: usize,
+    visibility: Visibility,
+    light_links: LightLinks,
 }
 
 impl<'a> Facet<'a> {
@@ -15,8 +17,22 @@ impl<'a> Facet<'a> {
             geom: FacetGeometry::new(tri, flat_shading),
             material,
             surface_id: 0,
+            visibility: Visibility::all(),
+            light_links: LightLinks::All,
         }
     }
+
+    /// Change which kind of rays can see this facet.
+    pub fn with_visibility(mut self, visibility: Visibility) -> Self {
+        self.visibility = visibility;
+        self
+    }
+
+    /// Change the set of lights that illuminate this facet.
+    pub fn with_light_links(mut self, light_links: LightLinks) -> Self {
+        self.light_links = light_links;
+        self
+    }
 }
 
 impl Object for Facet<'_> {
@@ -27,6 +43,18 @@ impl Object for Facet<'_> {
     fn set_surface_id(&mut self, sfid: usize) {
         self.surface_id = sfid;
     }
+
+    fn surface_id(&self) -> usize {
+        self.surface_id
+    }
+
+    fn visibility(&self) -> Visibility {
+        self.visibility
+    }
+
+    fn is_lit_by(&self, light_id: usize) -> bool {
+        self.light_links.contains(light_id)
+    }
 }
 
 impl Shape for Facet<'_> {
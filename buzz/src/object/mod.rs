@@ -1,7 +1,12 @@
 pub mod facet;
 pub mod simple_object;
+pub mod triangle_mesh;
+pub mod visibility;
 
-use std::ops::{Deref, DerefMut};
+use std::{
+    ops::{Deref, DerefMut},
+    sync::Arc,
+};
 
 use geo::{
     spatial_index::{Intersection, Shape},
@@ -10,8 +15,10 @@ use geo::{
 
 pub use facet::Facet;
 pub use simple_object::SimpleObject;
+pub use triangle_mesh::TriangleMesh;
+pub use visibility::{LightLinks, RayKind, Visibility};
 
-use crate::material::Material;
+use crate::{material::Material, sampler::Sampler};
 
 /// An `Object` that can be rendered.
 pub trait Object: Shape<Intersection = Hit> + Surface + Sync + Send {
@@ -22,6 +29,21 @@ pub trait Object: Shape<Intersection = Hit> + Surface + Sync + Send {
     /// returned in the `surface_id` field of `Hit` when this `Object` is
     /// intersected.
     fn set_surface_id(&mut self, id: usize);
+
+    /// Getter for the id set by `set_surface_id`.
+    fn surface_id(&self) -> usize;
+
+    /// Which kind of rays can see this `Object`. By default an `Object` is
+    /// visible to all of them.
+    fn visibility(&self) -> Visibility {
+        Visibility::all()
+    }
+
+    /// Whether this `Object` receives direct lighting from the light with the
+    /// given surface id. By default all the lights illuminate an `Object`.
+    fn is_lit_by(&self, _light_id: usize) -> bool {
+        true
+    }
 }
 
 /// A `Surface` is an object that can be shaded.
@@ -29,6 +51,19 @@ pub trait Surface: std::fmt::Debug {
     /// Calculate the normal for the given point `p`. This method should never
     /// be called if the `Surface` does not intersect it.
     fn normal_at(&self, p: Vec3) -> Vec3;
+
+    /// The area of the `Surface`, if it's finite and known. Together with
+    /// `sample` it's used to sample the surface of area lights.
+    fn area(&self) -> Option<f64> {
+        None
+    }
+
+    /// Sample a point uniformly distributed on the `Surface`, that is with
+    /// probability density `1 / area`. Lights whose surface can't be sampled
+    /// are sampled through their bounding sphere instead.
+    fn sample(&self, _sampler: &mut dyn Sampler) -> Option<Vec3> {
+        None
+    }
 }
 
 /// An `Hit` represents an intersection between a `Ray` and the shapes in a
@@ -41,10 +76,24 @@ pub struct Hit {
     /// the `Surface` the `Ray` hit
     pub surface_id: usize,
 
+    /// the index of the primitive inside the `Surface` that was hit, for
+    /// example the triangle of a `TriangleMesh`. It's always 0 for surfaces
+    /// made of a single primitive.
+    pub primitive: usize,
+
     /// point and normal corresponding at the given `t`. This doesn't need to be
     /// set, but in case they were already calculated as part of the
     /// intersection check a recalculation is avoided this way.
     pub point_and_normal: Option<(Vec3, Vec3)>,
+
+    /// the orbit trap in [0, 1] of the hit point for fractal surfaces, used by
+    /// `Material::OrbitTrap` for coloring.
+    pub orbit_trap: Option<f64>,
+
+    /// the `Material` of the part of the `Surface` that was hit, for surfaces
+    /// made of many materials like CSG shapes. When `None` the material of the
+    /// `Object` is used.
+    pub material: Option<Material>,
 }
 
 impl Hit {
@@ -53,6 +102,9 @@ impl Hit {
             t,
             point_and_normal,
             surface_id: 0,
+            primitive: 0,
+            orbit_trap: None,
+            material: None,
         }
     }
 }
@@ -74,6 +126,18 @@ where
     fn set_surface_id(&mut self, id: usize) {
         self.deref_mut().set_surface_id(id)
     }
+
+    fn surface_id(&self) -> usize {
+        self.deref().surface_id()
+    }
+
+    fn visibility(&self) -> Visibility {
+        self.deref().visibility()
+    }
+
+    fn is_lit_by(&self, light_id: usize) -> bool {
+        self.deref().is_lit_by(light_id)
+    }
 }
 
 impl<T> Surface for Box<T>
@@ -83,4 +147,29 @@ where
     fn normal_at(&self, p: Vec3) -> Vec3 {
         self.deref().normal_at(p)
     }
+
+    fn area(&self) -> Option<f64> {
+        self.deref().area()
+    }
+
+    fn sample(&self, sampler: &mut dyn Sampler) -> Option<Vec3> {
+        self.deref().sample(sampler)
+    }
+}
+
+impl<T> Surface for Arc<T>
+where
+    T: Surface + ?Sized,
+{
+    fn normal_at(&self, p: Vec3) -> Vec3 {
+        self.deref().normal_at(p)
+    }
+
+    fn area(&self) -> Option<f64> {
+        self.deref().area()
+    }
+
+    fn sample(&self, sampler: &mut dyn Sampler) -> Option<Vec3> {
+        self.deref().sample(sampler)
+    }
 }
@@ -1,12 +1,14 @@
 use geo::{ray::Ray, spatial_index::Shape, Aabb, Vec3};
 
-use crate::{material::Material, Hit, Object, Surface};
+use crate::{material::Material, sampler::Sampler, Hit, LightLinks, Object, Surface, Visibility};
 
 #[derive(Debug)]
 pub struct SimpleObject<S> {
     geom: S,
     material: Material,
     surface_id: usize,
+    visibility: Visibility,
+    light_links: LightLinks,
 }
 
 impl<G> SimpleObject<G> {
@@ -15,8 +17,22 @@ impl<G> SimpleObject<G> {
             geom,
             material,
             surface_id: 0,
+            visibility: Visibility::all(),
+            light_links: LightLinks::All,
         }
     }
+
+    /// Change which kind of rays can see this object.
+    pub fn with_visibility(mut self, visibility: Visibility) -> Self {
+        self.visibility = visibility;
+        self
+    }
+
+    /// Change the set of lights that illuminate this object.
+    pub fn with_light_links(mut self, light_links: LightLinks) -> Self {
+        self.light_links = light_links;
+        self
+    }
 }
 
 impl<S> Object for SimpleObject<S>
@@ -30,6 +46,18 @@ where
     fn set_surface_id(&mut self, id: usize) {
         self.surface_id = id;
     }
+
+    fn surface_id(&self) -> usize {
+        self.surface_id
+    }
+
+    fn visibility(&self) -> Visibility {
+        self.visibility
+    }
+
+    fn is_lit_by(&self, light_id: usize) -> bool {
+        self.light_links.contains(light_id)
+    }
 }
 
 impl<S> Surface for SimpleObject<S>
@@ -39,6 +67,14 @@ where
     fn normal_at(&self, p: Vec3) -> Vec3 {
         self.geom.normal_at(p)
     }
+
+    fn area(&self) -> Option<f64> {
+        self.geom.area()
+    }
+
+    fn sample(&self, sampler: &mut dyn Sampler) -> Option<Vec3> {
+        self.geom.sample(sampler)
+    }
 }
 
 impl<S> Shape for SimpleObject<S>
@@ -0,0 +1,640 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+use image::GrayImage;
+
+use geo::{mesh::obj::Obj, mesh::Mesh, ray::Ray, spatial_index::Shape, Aabb, Triangle, Vec3};
+
+use crate::{material::Material, Hit, LightLinks, Object, Surface, Visibility};
+
+/// Maximum number of triangles in a leaf of the bvh of a `TriangleMesh`.
+const MAX_LEAF_TRIANGLES: usize = 4;
+
+/// A mesh of triangles sharing indexed vertices that is rendered as a single
+/// `Object` with one surface id. Each vertex has a normal that is smoothly
+/// interpolated across the triangles and optionally texture coordinates.
+///
+/// The triangles are stored in an internal bounding volume hierarchy and the
+/// index of the triangle that was hit is reported in `Hit::primitive`.
+#[derive(Debug, Clone)]
+pub struct TriangleMesh {
+    positions: Vec<Vec3>,
+    normals: Vec<Vec3>,
+    uvs: Option<Vec<(f64, f64)>>,
+    triangles: Vec<[u32; 3]>,
+
+    // the nodes of the bvh are laid out in depth first order, that is the left
+    // child of a branch always follows it. The leaves reference ranges of
+    // `order` which contains the indices of the triangles.
+    nodes: Vec<BvhNode>,
+    order: Vec<u32>,
+
+    material: Material,
+    surface_id: usize,
+    visibility: Visibility,
+    light_links: LightLinks,
+}
+
+#[derive(Debug, Clone)]
+enum BvhNode {
+    Branch { bbox: Aabb, right: u32 },
+    Leaf { bbox: Aabb, start: u32, end: u32 },
+}
+
+impl TriangleMesh {
+    /// Create a new `TriangleMesh` from the positions of the vertices and the
+    /// triangles made of indices into them. The vertex normals are calculated
+    /// by averaging the normals of the triangles around each vertex weighted
+    /// by their area.
+    pub fn new(positions: Vec<Vec3>, triangles: Vec<[u32; 3]>, material: Material) -> Self {
+        let mut mesh = TriangleMesh {
+            normals: vertex_normals(&positions, &triangles),
+            positions,
+            uvs: None,
+            triangles,
+            nodes: vec![],
+            order: vec![],
+            material,
+            surface_id: 0,
+            visibility: Visibility::all(),
+            light_links: LightLinks::All,
+        };
+        mesh.build_bvh();
+        mesh
+    }
+
+    /// Create a `TriangleMesh` from the triangles of a generic `Mesh` merging
+    /// the vertices that have exactly the same position.
+    pub fn from_mesh(mesh: &dyn Mesh, material: Material) -> Self {
+        let mut positions = vec![];
+        let mut indices = HashMap::new();
+
+        let mut index_of = |p: Vec3| {
+            *indices
+                .entry((p.x.to_bits(), p.y.to_bits(), p.z.to_bits()))
+                .or_insert_with(|| {
+                    positions.push(p);
+                    u32::try_from(positions.len() - 1).unwrap()
+                })
+        };
+
+        let triangles = mesh
+            .triangles()
+            .map(|t| [index_of(t.a), index_of(t.b), index_of(t.c)])
+            .collect();
+
+        TriangleMesh::new(positions, triangles, material)
+    }
+
+    /// Create a `TriangleMesh` from an `Obj` using its vertex normals and
+    /// texture coordinates if all the vertices have them.
+    pub fn from_obj(obj: &Obj, material: Material) -> Self {
+        let mut corners = vec![];
+        let mut indices = HashMap::new();
+
+        let triangles = obj
+            .faces()
+            .map(|face| {
+                face.map(|v| {
+                    *indices.entry(v).or_insert_with(|| {
+                        corners.push(v);
+                        u32::try_from(corners.len() - 1).unwrap()
+                    })
+                })
+            })
+            .collect();
+
+        let positions = corners.iter().map(|v| obj.vertices()[v.position]);
+        let mut mesh = TriangleMesh::new(positions.collect(), triangles, material);
+
+        let normals = corners
+            .iter()
+            .map(|v| Some(obj.normals()[v.normal?].normalized()))
+            .collect::<Option<Vec<_>>>();
+        if let Some(normals) = normals {
+            mesh = mesh.with_normals(normals);
+        }
+
+        let uvs = corners
+            .iter()
+            .map(|v| Some(obj.uvs()[v.uv?]))
+            .collect::<Option<Vec<_>>>();
+        if let Some(uvs) = uvs {
+            mesh = mesh.with_uvs(uvs);
+        }
+
+        mesh
+    }
+
+    /// Use the given vertex normals instead of the calculated ones. There must
+    /// be a normal for each vertex.
+    pub fn with_normals(mut self, normals: Vec<Vec3>) -> Self {
+        assert_eq!(normals.len(), self.positions.len());
+
+        self.normals = normals;
+        self
+    }
+
+    /// Set the texture coordinates of each vertex.
+    pub fn with_uvs(mut self, uvs: Vec<(f64, f64)>) -> Self {
+        assert_eq!(uvs.len(), self.positions.len());
+
+        self.uvs = Some(uvs);
+        self
+    }
+
+    /// Change which kind of rays can see this mesh.
+    pub fn with_visibility(mut self, visibility: Visibility) -> Self {
+        self.visibility = visibility;
+        self
+    }
+
+    /// Change the set of lights that illuminate this mesh.
+    pub fn with_light_links(mut self, light_links: LightLinks) -> Self {
+        self.light_links = light_links;
+        self
+    }
+
+    /// Split each triangle in 4 smaller ones `subdivisions` times and then move
+    /// each vertex along its normal by the amount returned by `displacement`
+    /// given its position and texture coordinates. The normals are
+    /// recalculated from the displaced triangles.
+    ///
+    /// Vertices that are duplicated, for example along uv seams, are displaced
+    /// independently and cracks appear if `displacement` doesn't agree on them.
+    pub fn displaced(
+        mut self,
+        subdivisions: u32,
+        displacement: impl Fn(Vec3, Option<(f64, f64)>) -> f64,
+    ) -> Self {
+        for _ in 0..subdivisions {
+            self.subdivide();
+        }
+
+        for (i, p) in self.positions.iter_mut().enumerate() {
+            let uv = self.uvs.as_ref().map(|uvs| uvs[i]);
+            *p += self.normals[i] * displacement(*p, uv);
+        }
+
+        self.normals = vertex_normals(&self.positions, &self.triangles);
+        self.build_bvh();
+
+        self
+    }
+
+    /// Like `displaced`, but the displacement is read from a height map
+    /// using the texture coordinates of the vertices. Black is no displacement
+    /// while white is `scale`. The mesh must have texture coordinates.
+    pub fn displaced_by_image(self, subdivisions: u32, img: &GrayImage, scale: f64) -> Self {
+        assert!(
+            self.uvs.is_some(),
+            "displacing by image requires texture coordinates"
+        );
+
+        self.displaced(subdivisions, |_, uv| {
+            let (u, v) = uv.unwrap();
+            sample_height(img, u, v) * scale
+        })
+    }
+
+    /// Return the number of triangles in the mesh.
+    pub fn len(&self) -> usize {
+        self.triangles.len()
+    }
+
+    /// Return whether the mesh has no triangles.
+    pub fn is_empty(&self) -> bool {
+        self.triangles.is_empty()
+    }
+
+    /// Return the triangle with the given index.
+    pub fn triangle(&self, primitive: usize) -> Triangle {
+        let [a, b, c] = self.triangles[primitive].map(|i| self.positions[i as usize]);
+        Triangle::new(a, b, c)
+    }
+
+    /// Interpolate the texture coordinates of the point `p` lying on the
+    /// triangle with the given index. Return `None` if the mesh has no texture
+    /// coordinates or `p` is outside the triangle.
+    pub fn uv_at(&self, primitive: usize, p: Vec3) -> Option<(f64, f64)> {
+        let uvs = self.uvs.as_ref()?;
+        let bary = self.triangle(primitive).barycentric(&p)?;
+
+        let [a, b, c] = self.triangles[primitive].map(|i| uvs[i as usize]);
+        Some((
+            a.0 * bary.x + b.0 * bary.y + c.0 * bary.z,
+            a.1 * bary.x + b.1 * bary.y + c.1 * bary.z,
+        ))
+    }
+
+    /// Split each triangle in 4 by adding a vertex at the middle of each edge.
+    /// The vertices are shared between adjacent triangles so that the mesh
+    /// stays watertight.
+    fn subdivide(&mut self) {
+        let old_triangles = std::mem::take(&mut self.triangles);
+
+        let mut midpoints = HashMap::new();
+        let mut midpoint = |i: u32, j: u32| {
+            *midpoints.entry((i.min(j), i.max(j))).or_insert_with(|| {
+                let (i, j) = (i as usize, j as usize);
+
+                self.positions
+                    .push((self.positions[i] + self.positions[j]) / 2.0);
+
+                let n = self.normals[i] + self.normals[j];
+                self.normals
+                    .push(if n.norm2() > 0.0 { n.normalized() } else { n });
+
+                if let Some(uvs) = &mut self.uvs {
+                    uvs.push(((uvs[i].0 + uvs[j].0) / 2.0, (uvs[i].1 + uvs[j].1) / 2.0));
+                }
+
+                u32::try_from(self.positions.len() - 1).unwrap()
+            })
+        };
+
+        let mut triangles = Vec::with_capacity(old_triangles.len() * 4);
+        for [a, b, c] in old_triangles {
+            let (ab, bc, ca) = (midpoint(a, b), midpoint(b, c), midpoint(c, a));
+
+            triangles.extend([[a, ab, ca], [ab, b, bc], [ca, bc, c], [ab, bc, ca]]);
+        }
+
+        self.triangles = triangles;
+    }
+
+    fn build_bvh(&mut self) {
+        let bboxes = (0..self.triangles.len())
+            .map(|i| self.triangle(i).bbox())
+            .collect::<Vec<_>>();
+
+        let mut order = (0..u32::try_from(self.triangles.len()).unwrap()).collect::<Vec<_>>();
+        self.nodes = vec![];
+
+        if !order.is_empty() {
+            build_node(&mut self.nodes, &mut order, 0, &bboxes);
+        }
+        self.order = order;
+    }
+
+    /// Return the distance at which the ray enters the bounding box of the
+    /// given node if it does so before `max_t`.
+    fn node_entry(&self, node: usize, ray: &Ray, max_t: f64) -> Option<f64> {
+        match self.nodes[node].bbox().ray_intersection(ray) {
+            Some((t0, t1)) if t1 >= 0.0 && t0 <= max_t => Some(t0),
+            _ => None,
+        }
+    }
+}
+
+impl BvhNode {
+    fn bbox(&self) -> &Aabb {
+        match self {
+            BvhNode::Branch { bbox, .. } | BvhNode::Leaf { bbox, .. } => bbox,
+        }
+    }
+}
+
+/// Recursively build the node for the triangles in `order` appending it and
+/// its children to `nodes`. `offset` is the position of `order` in the whole
+/// list of triangles.
+fn build_node(nodes: &mut Vec<BvhNode>, order: &mut [u32], offset: usize, bboxes: &[Aabb]) {
+    let mut bbox = bboxes[order[0] as usize].clone();
+    for &i in &order[1..] {
+        bbox = bbox.union(&bboxes[i as usize]);
+    }
+
+    if order.len() <= MAX_LEAF_TRIANGLES {
+        nodes.push(BvhNode::Leaf {
+            bbox,
+            start: u32::try_from(offset).unwrap(),
+            end: u32::try_from(offset + order.len()).unwrap(),
+        });
+        return;
+    }
+
+    // split at the median along the axis where the centers are more spread
+    let centers = Aabb::from_iter(order.iter().map(|&i| bboxes[i as usize].center())).unwrap();
+    let dims = centers.dimensions();
+    let axis = if dims.x > dims.y && dims.x > dims.z {
+        geo::Axis::X
+    } else if dims.y > dims.z {
+        geo::Axis::Y
+    } else {
+        geo::Axis::Z
+    };
+
+    let mid = order.len() / 2;
+    order.select_nth_unstable_by(mid, |&i, &j| {
+        let ci = bboxes[i as usize].center()[axis];
+        let cj = bboxes[j as usize].center()[axis];
+        ci.partial_cmp(&cj).unwrap()
+    });
+
+    let node = nodes.len();
+    nodes.push(BvhNode::Branch { bbox, right: 0 });
+
+    let (left, right) = order.split_at_mut(mid);
+    build_node(nodes, left, offset, bboxes);
+
+    let right_node = u32::try_from(nodes.len()).unwrap();
+    build_node(nodes, right, offset + mid, bboxes);
+
+    if let BvhNode::Branch { right, .. } = &mut nodes[node] {
+        *right = right_node;
+    }
+}
+
+/// Calculate the normal of each vertex by averaging the normals of the
+/// triangles around it weighted by their area.
+fn vertex_normals(positions: &[Vec3], triangles: &[[u32; 3]]) -> Vec<Vec3> {
+    let mut normals = vec![Vec3::zero(); positions.len()];
+    for tri in triangles {
+        let [a, b, c] = tri.map(|i| positions[i as usize]);
+
+        // the norm of the cross product is twice the area of the triangle
+        let n = (b - a).cross(c - a);
+        for &i in tri {
+            normals[i as usize] += n;
+        }
+    }
+    for n in &mut normals {
+        if n.norm2() > 0.0 {
+            n.normalize();
+        }
+    }
+
+    normals
+}
+
+/// Bilinearly interpolate the intensity of the image at the given texture
+/// coordinates returning a value between 0 and 1. The v coordinate goes from
+/// the bottom to the top of the image.
+fn sample_height(img: &GrayImage, u: f64, v: f64) -> f64 {
+    let (w, h) = img.dimensions();
+    let px = |x: u32, y: u32| f64::from(img.get_pixel(x, y).0[0]) / 255.0;
+
+    let x = u.clamp(0.0, 1.0) * f64::from(w - 1);
+    let y = (1.0 - v.clamp(0.0, 1.0)) * f64::from(h - 1);
+
+    let (x0, y0) = (x.floor() as u32, y.floor() as u32);
+    let (x1, y1) = ((x0 + 1).min(w - 1), (y0 + 1).min(h - 1));
+    let (tx, ty) = (x - f64::from(x0), y - f64::from(y0));
+
+    let top = px(x0, y0) * (1.0 - tx) + px(x1, y0) * tx;
+    let bottom = px(x0, y1) * (1.0 - tx) + px(x1, y1) * tx;
+
+    top * (1.0 - ty) + bottom * ty
+}
+
+impl Object for TriangleMesh {
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn set_surface_id(&mut self, id: usize) {
+        self.surface_id = id;
+    }
+
+    fn surface_id(&self) -> usize {
+        self.surface_id
+    }
+
+    fn visibility(&self) -> Visibility {
+        self.visibility
+    }
+
+    fn is_lit_by(&self, light_id: usize) -> bool {
+        self.light_links.contains(light_id)
+    }
+}
+
+impl Shape for TriangleMesh {
+    type Intersection = Hit;
+
+    fn intersection(&self, ray: &Ray) -> Option<Self::Intersection> {
+        let mut closest: Option<(usize, f64)> = None;
+        let best_t = |closest: Option<(usize, f64)>| closest.map_or(f64::INFINITY, |(_, t)| t);
+
+        // the nodes are visited front to back and skipped as soon as they
+        // start farther than the closest hit found so far
+        let mut stack = vec![];
+        if !self.nodes.is_empty() {
+            if let Some(t0) = self.node_entry(0, ray, f64::INFINITY) {
+                stack.push((0, t0));
+            }
+        }
+
+        while let Some((n, t0)) = stack.pop() {
+            if t0 > best_t(closest) {
+                continue;
+            }
+
+            match self.nodes[n] {
+                BvhNode::Branch { right, .. } => {
+                    let max_t = best_t(closest);
+                    let left = self.node_entry(n + 1, ray, max_t).map(|t| (n + 1, t));
+                    let right = self
+                        .node_entry(right as usize, ray, max_t)
+                        .map(|t| (right as usize, t));
+
+                    match (left, right) {
+                        (Some(l), Some(r)) => {
+                            let (near, far) = if l.1 <= r.1 { (l, r) } else { (r, l) };
+                            stack.push(far);
+                            stack.push(near);
+                        }
+                        (Some(child), None) | (None, Some(child)) => stack.push(child),
+                        (None, None) => {}
+                    }
+                }
+                BvhNode::Leaf { start, end, .. } => {
+                    for &i in &self.order[start as usize..end as usize] {
+                        let i = i as usize;
+
+                        if let Some(t) = self.triangle(i).intersection(ray) {
+                            if t < best_t(closest) {
+                                closest = Some((i, t));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        let (primitive, t) = closest?;
+        let p = ray.point_at(t);
+        let triangle = self.triangle(primitive);
+
+        // the barycentric coordinates might be slightly outside of the triangle
+        // along the edges due to rounding, in that case use the flat normal
+        let n = match triangle.barycentric(&p) {
+            Some(bary) => {
+                let [na, nb, nc] = self.triangles[primitive].map(|i| self.normals[i as usize]);
+                (na * bary.x + nb * bary.y + nc * bary.z).normalized()
+            }
+            None => triangle.normal(),
+        };
+
+        let mut hit = Hit::new(t, Some((p, n)));
+        hit.surface_id = self.surface_id;
+        hit.primitive = primitive;
+
+        Some(hit)
+    }
+
+    fn bbox(&self) -> Aabb {
+        self.nodes
+            .first()
+            .map_or_else(|| Aabb::new(Vec3::zero()), |node| node.bbox().clone())
+    }
+}
+
+impl Surface for TriangleMesh {
+    fn normal_at(&self, _p: Vec3) -> Vec3 {
+        // the normal is always calculated during the intersection because it
+        // depends on the triangle that was hit
+        unreachable!()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intersection() {
+        // a pyramid with a square base
+        let mesh = TriangleMesh::new(
+            vec![
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec3::new(1.0, 0.0, 0.0),
+                Vec3::new(1.0, 1.0, 0.0),
+                Vec3::new(0.0, 1.0, 0.0),
+                Vec3::new(0.5, 0.5, 1.0),
+            ],
+            vec![
+                [0, 2, 1],
+                [0, 3, 2],
+                [0, 1, 4],
+                [1, 2, 4],
+                [2, 3, 4],
+                [3, 0, 4],
+            ],
+            Material::lambertian(Vec3::replicate(1.0)),
+        )
+        .with_uvs(vec![
+            (0.0, 0.0),
+            (1.0, 0.0),
+            (1.0, 1.0),
+            (0.0, 1.0),
+            (0.5, 0.5),
+        ]);
+
+        let ray = Ray::new(Vec3::new(0.25, 0.5, -1.0), Vec3::new(0.0, 0.0, 1.0));
+        let hit = mesh.intersection(&ray).unwrap();
+        assert_eq!(hit.t, 1.0);
+        assert_eq!(hit.primitive, 1);
+        assert_eq!(
+            mesh.uv_at(hit.primitive, ray.point_at(hit.t)),
+            Some((0.25, 0.5))
+        );
+
+        // the normal at the apex is the average of the normals of the sides
+        let ray = Ray::new(Vec3::new(0.5, 0.5, 2.0), Vec3::new(0.0, 0.0, -1.0));
+        let hit = mesh.intersection(&ray).unwrap();
+        let (p, n) = hit.point_and_normal.unwrap();
+        assert!(p.dist(Vec3::new(0.5, 0.5, 1.0)) < 1e-9);
+        assert!(n.dist(Vec3::new(0.0, 0.0, 1.0)) < 1e-9);
+
+        let ray = Ray::new(Vec3::new(2.0, 0.5, -1.0), Vec3::new(0.0, 0.0, 1.0));
+        assert!(mesh.intersection(&ray).is_none());
+    }
+
+    #[test]
+    fn test_displaced() {
+        let square = TriangleMesh::new(
+            vec![
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec3::new(1.0, 0.0, 0.0),
+                Vec3::new(1.0, 1.0, 0.0),
+                Vec3::new(0.0, 1.0, 0.0),
+            ],
+            vec![[0, 1, 2], [0, 2, 3]],
+            Material::lambertian(Vec3::replicate(1.0)),
+        )
+        .with_uvs(vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]);
+
+        let mesh = square.clone().displaced(2, |_, uv| uv.unwrap().0);
+        assert_eq!(mesh.len(), 32);
+
+        // 5x5 grid of vertices since the midpoints are shared
+        assert_eq!(mesh.positions.len(), 25);
+        assert!(mesh.positions.iter().all(|p| (p.z - p.x).abs() < 1e-9));
+        let n = Vec3::new(-1.0, 0.0, 1.0).normalized();
+        assert!(mesh.normals.iter().all(|m| m.dist(n) < 1e-9));
+
+        let ray = Ray::new(Vec3::new(0.3, 0.6, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        let hit = mesh.intersection(&ray).unwrap();
+        assert!((hit.t - 4.7).abs() < 1e-9);
+
+        let img = GrayImage::from_fn(2, 2, |_, y| image::Luma([if y == 0 { 255 } else { 0 }]));
+        let mesh = square.displaced_by_image(1, &img, 2.0);
+        assert!(mesh
+            .positions
+            .iter()
+            .all(|p| (p.z - p.y * 2.0).abs() < 1e-9));
+    }
+
+    #[test]
+    fn test_closest_hit() {
+        let grid = TriangleMesh::new(
+            vec![
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec3::new(1.0, 0.0, 0.0),
+                Vec3::new(1.0, 1.0, 0.0),
+                Vec3::new(0.0, 1.0, 0.0),
+            ],
+            vec![[0, 1, 2], [0, 2, 3]],
+            Material::lambertian(Vec3::replicate(1.0)),
+        );
+
+        // a wavy surface made of many triangles that slanted rays cross more
+        // than once
+        let mesh = grid.displaced(4, |p, _| (p.x * 20.0).sin() * 0.3);
+        assert_eq!(mesh.len(), 512);
+
+        for i in 0..20 {
+            for j in 0..20 {
+                let origin = Vec3::new(f64::from(i) / 20.0 - 0.5, f64::from(j) / 20.0, 1.0);
+                let ray = Ray::new(origin, Vec3::new(0.7, 0.1, -1.0).normalized());
+
+                let expected = (0..mesh.len())
+                    .filter_map(|t| Some((t, mesh.triangle(t).intersection(&ray)?)))
+                    .min_by(|(_, t0), (_, t1)| t0.partial_cmp(t1).unwrap());
+
+                let hit = mesh.intersection(&ray);
+                assert_eq!(hit.map(|h| (h.primitive, h.t)), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_mesh() {
+        let obj = Obj::load(
+            "v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+f 1 2 3
+f 1 3 4"
+                .as_bytes(),
+        )
+        .unwrap();
+
+        let mesh = TriangleMesh::from_mesh(&obj, Material::lambertian(Vec3::zero()));
+        assert_eq!(mesh.len(), 2);
+        assert_eq!(mesh.positions.len(), 4);
+        assert_eq!(mesh.normals, vec![Vec3::new(0.0, 0.0, 1.0); 4]);
+    }
+}
@@ -0,0 +1,109 @@
+/// The kind of a `Ray` that is being traced through a `Scene`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RayKind {
+    /// Primary ray that starts from the `Camera`.
+    Camera,
+
+    /// Ray cast towards a light to check whether a point is in shadow.
+    Shadow,
+
+    /// Secondary ray spawned by a bounce on a material, that is reflections,
+    /// refractions and diffuse inter-reflections.
+    Reflection,
+}
+
+/// Flags that control which kind of rays can see an `Object`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Visibility {
+    /// whether the object is directly visible from the camera.
+    pub camera: bool,
+
+    /// whether the object casts shadows.
+    pub shadow: bool,
+
+    /// whether the object shows up in reflections, refractions and indirect
+    /// lighting.
+    pub reflection: bool,
+}
+
+/// The set of lights that illuminate an `Object`. Lights are identified by
+/// their surface id that is the index in which they were pushed into
+/// `SceneObjects`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum LightLinks {
+    /// The object is lit by all the lights in the scene.
+    #[default]
+    All,
+
+    /// The object is lit only by the given lights.
+    Only(Vec<usize>),
+
+    /// The object is lit by all the lights in the scene but the given ones.
+    Except(Vec<usize>),
+}
+
+impl Visibility {
+    /// `Visibility` where the object can be seen by all kind of rays.
+    pub const fn all() -> Self {
+        Visibility {
+            camera: true,
+            shadow: true,
+            reflection: true,
+        }
+    }
+
+    /// Check whether a `Ray` of the given kind can see the object.
+    pub fn is_visible_to(&self, kind: RayKind) -> bool {
+        match kind {
+            RayKind::Camera => self.camera,
+            RayKind::Shadow => self.shadow,
+            RayKind::Reflection => self.reflection,
+        }
+    }
+}
+
+impl Default for Visibility {
+    fn default() -> Self {
+        Visibility::all()
+    }
+}
+
+impl LightLinks {
+    /// Check whether the light with the given id is linked.
+    pub fn contains(&self, light_id: usize) -> bool {
+        match self {
+            LightLinks::All => true,
+            LightLinks::Only(ids) => ids.contains(&light_id),
+            LightLinks::Except(ids) => !ids.contains(&light_id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_visibility() {
+        let v = Visibility {
+            camera: false,
+            ..Visibility::default()
+        };
+        assert!(!v.is_visible_to(RayKind::Camera));
+        assert!(v.is_visible_to(RayKind::Shadow));
+        assert!(v.is_visible_to(RayKind::Reflection));
+    }
+
+    #[test]
+    fn test_light_links() {
+        assert!(LightLinks::All.contains(3));
+
+        let only = LightLinks::Only(vec![1, 3]);
+        assert!(only.contains(3));
+        assert!(!only.contains(2));
+
+        let except = LightLinks::Except(vec![1, 3]);
+        assert!(!except.contains(3));
+        assert!(except.contains(2));
+    }
+}
@@ -0,0 +1,115 @@
+use geo::{
+    mat4::{Mat4, Transform},
+    Aabb,
+};
+
+use crate::{Hit, Ray, Shape, Surface, Vec3};
+
+/// A geometry that moves from a `start` transformation at time 0 to an `end`
+/// transformation at time 1. The transformation used for each `Ray` is the
+/// interpolation of the two at the `Ray`'s time, therefore the camera shutter
+/// should be open between 0 and 1 to render the whole motion.
+///
+/// Note that the matrices are interpolated linearly, hence large rotations
+/// should be split in multiple shorter movements.
+#[derive(Debug, PartialEq, Clone)]
+pub struct AnimatedGeometry<S> {
+    shape: S,
+    start: Mat4,
+    end: Mat4,
+}
+
+impl<S> AnimatedGeometry<S> {
+    pub fn new(shape: S, start: Mat4, end: Mat4) -> Self {
+        AnimatedGeometry { shape, start, end }
+    }
+
+    /// Return the transformation at the given time.
+    pub fn transform_at(&self, time: f64) -> Mat4 {
+        self.start.lerp(&self.end, time.clamp(0.0, 1.0))
+    }
+}
+
+impl<S> Shape for AnimatedGeometry<S>
+where
+    S: Shape<Intersection = Hit> + Surface,
+{
+    type Intersection = Hit;
+
+    fn intersection(&self, ray: &Ray) -> Option<Self::Intersection> {
+        let trans = self.transform_at(ray.time);
+        let inverse_trans = trans.inverse();
+
+        let transformed_ray = ray.transform(&inverse_trans);
+        let hit = self.shape.intersection(&transformed_ray)?;
+
+        let (p, n) = hit.point_and_normal.unwrap_or_else(|| {
+            let p = transformed_ray.point_at(hit.t);
+            (p, self.shape.normal_at(p))
+        });
+
+        let intersection = p.transform(&trans);
+        let tn = inverse_trans.transpose().transform_normal(&n);
+
+        Some(Hit {
+            t: intersection.dist(ray.origin) / ray.dir.norm(),
+            point_and_normal: Some((intersection, tn)),
+            ..hit
+        })
+    }
+
+    fn bbox(&self) -> Aabb {
+        // each point moves linearly between its start and end positions,
+        // therefore the union of the two boxes bounds the whole motion.
+        let bbox = self.shape.bbox();
+        bbox.transform(&self.start)
+            .union(&bbox.transform(&self.end))
+    }
+}
+
+impl<S> Surface for AnimatedGeometry<S>
+where
+    S: Surface,
+{
+    fn normal_at(&self, _p: Vec3) -> Vec3 {
+        unreachable!()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SphereGeometry;
+
+    #[test]
+    fn test_animated_geometry() {
+        let geom = AnimatedGeometry::new(
+            SphereGeometry::new(Vec3::zero(), 1.0),
+            Mat4::identity(),
+            Mat4::translate(Vec3::new(0.0, 4.0, 0.0)),
+        );
+
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        let hit = geom.intersection(&ray).unwrap();
+        assert!((hit.t - 4.0).abs() < 1e-9);
+        let (_, n) = hit.point_and_normal.unwrap();
+        assert!(n.dist(Vec3::new(0.0, 0.0, 1.0)) < 1e-9);
+
+        // at the end of the motion the sphere moved out of the way
+        assert!(geom.intersection(&ray.clone().with_time(1.0)).is_none());
+
+        let ray = Ray::new(Vec3::new(0.0, 4.0, 5.0), Vec3::new(0.0, 0.0, -1.0)).with_time(1.0);
+        let hit = geom.intersection(&ray).unwrap();
+        assert!((hit.t - 4.0).abs() < 1e-9);
+        let (p, _) = hit.point_and_normal.unwrap();
+        assert!(p.dist(Vec3::new(0.0, 4.0, 1.0)) < 1e-9);
+
+        // halfway the sphere is centered at y = 2
+        let ray = Ray::new(Vec3::new(0.0, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0)).with_time(0.5);
+        assert!((geom.intersection(&ray).unwrap().t - 2.0).abs() < 1e-9);
+
+        let bbox = geom.bbox();
+        assert_eq!(bbox.min(), Vec3::new(-1.0, -1.0, -1.0));
+        assert_eq!(bbox.max(), Vec3::new(1.0, 5.0, 1.0));
+    }
+}
@@ -0,0 +1,165 @@
+use geo::{plane, ray::Ray, spatial_index::Shape, util::poly, Aabb, Vec3};
+
+use crate::{Hit, Surface};
+
+/// A closed cone frustum going from `base` with radius `base_radius` to `top`
+/// with radius `top_radius`. A proper cone can be created by using a radius of
+/// 0 for the top.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ConeGeometry {
+    base: Vec3,
+    top: Vec3,
+    base_radius: f64,
+    top_radius: f64,
+
+    // unit vector from base to top and distance between them
+    axis: Vec3,
+    height: f64,
+}
+
+impl ConeGeometry {
+    pub fn new(base: Vec3, base_radius: f64, top: Vec3, top_radius: f64) -> Self {
+        let height = base.dist(top);
+
+        ConeGeometry {
+            base,
+            top,
+            base_radius: base_radius.abs(),
+            top_radius: top_radius.abs(),
+            axis: (top - base) / height,
+            height,
+        }
+    }
+
+    /// The radius of the frustum at the given height from the base.
+    fn radius_at(&self, h: f64) -> f64 {
+        self.base_radius + (self.top_radius - self.base_radius) * h / self.height
+    }
+
+    pub(crate) fn side_intersection(&self, ray: &Ray) -> Option<f64> {
+        let o = ray.origin - self.base;
+
+        let oh = o.dot(self.axis);
+        let dh = ray.dir.dot(self.axis);
+        let or = o - self.axis * oh;
+        let dr = ray.dir - self.axis * dh;
+
+        // |or + t dr|^2 = (r0 + k (oh + t dh))^2
+        let k = (self.top_radius - self.base_radius) / self.height;
+        let r0 = self.base_radius + k * oh;
+
+        let a = dr.norm2() - (k * dh).powi(2);
+        let b = 2.0 * (or.dot(dr) - k * dh * r0);
+        let c = or.norm2() - r0.powi(2);
+
+        poly::solve_quadratic(a, b, c).into_iter().find(|&t| {
+            let h = oh + t * dh;
+
+            // the quadratic also includes the mirrored cone past the apex
+            t > 1e-6 && (0.0..=self.height).contains(&h) && self.radius_at(h) >= 0.0
+        })
+    }
+
+    fn cap_intersection(&self, ray: &Ray, center: Vec3, radius: f64) -> Option<f64> {
+        if radius <= 0.0 {
+            return None;
+        }
+
+        let t = plane::intersection(center, self.axis, ray)?;
+        if ray.point_at(t).dist2(center) > radius.powi(2) {
+            return None;
+        }
+
+        Some(t)
+    }
+}
+
+impl Shape for ConeGeometry {
+    type Intersection = Hit;
+
+    fn intersection(&self, ray: &Ray) -> Option<Self::Intersection> {
+        let t = [
+            self.side_intersection(ray),
+            self.cap_intersection(ray, self.base, self.base_radius),
+            self.cap_intersection(ray, self.top, self.top_radius),
+        ]
+        .iter()
+        .flatten()
+        .fold(f64::INFINITY, |t0, &t1| t0.min(t1));
+
+        if t.is_infinite() {
+            return None;
+        }
+
+        Some(Hit::new(t, None))
+    }
+
+    fn bbox(&self) -> Aabb {
+        disc_bbox(self.base, self.axis, self.base_radius).union(&disc_bbox(
+            self.top,
+            self.axis,
+            self.top_radius,
+        ))
+    }
+}
+
+impl Surface for ConeGeometry {
+    fn normal_at(&self, p: Vec3) -> Vec3 {
+        const EPS: f64 = 1e-6;
+
+        let d = p - self.base;
+        let h = d.dot(self.axis);
+        let r = d - self.axis * h;
+
+        let inside = r.norm() < self.radius_at(h) - EPS;
+        if h < EPS && inside {
+            return -self.axis;
+        }
+        if h > self.height - EPS && inside {
+            return self.axis;
+        }
+
+        // gradient of |r| - radius_at(h)
+        let k = (self.top_radius - self.base_radius) / self.height;
+        (r.normalized() - self.axis * k).normalized()
+    }
+}
+
+/// Bounding box of a disc with the given `center`, `normal` and `radius`. The
+/// extent of the disc along each axis depends on how much it is tilted wrt it.
+pub(crate) fn disc_bbox(center: Vec3, normal: Vec3, radius: f64) -> Aabb {
+    let e = Vec3::new(
+        (1.0 - normal.x.powi(2)).max(0.0).sqrt(),
+        (1.0 - normal.y.powi(2)).max(0.0).sqrt(),
+        (1.0 - normal.z.powi(2)).max(0.0).sqrt(),
+    ) * radius;
+
+    Aabb::new(center - e).expanded(center + e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intersection() {
+        let cone = ConeGeometry::new(Vec3::zero(), 1.0, Vec3::new(0.0, 0.0, 2.0), 0.0);
+
+        // side
+        let r = Ray::new(Vec3::new(-5.0, 0.0, 1.0), Vec3::new(1.0, 0.0, 0.0));
+        let t = cone.intersection(&r).unwrap().t;
+        assert!((t - 4.5).abs() < 1e-9);
+        let n = cone.normal_at(r.point_at(t));
+        assert!(n.dist(Vec3::new(-2.0, 0.0, 1.0).normalized()) < 1e-9);
+
+        // base cap
+        let r = Ray::new(Vec3::new(0.5, 0.0, -2.0), Vec3::new(0.0, 0.0, 1.0));
+        let t = cone.intersection(&r).unwrap().t;
+        assert!((t - 2.0).abs() < 1e-9);
+        assert_eq!(cone.normal_at(r.point_at(t)), Vec3::new(0.0, 0.0, -1.0));
+
+        // the mirrored cone above the apex must be ignored
+        let r = Ray::new(Vec3::new(-5.0, 0.0, 3.0), Vec3::new(1.0, 0.0, 0.0));
+        assert!(cone.intersection(&r).is_none());
+    }
+}
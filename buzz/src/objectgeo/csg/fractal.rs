@@ -0,0 +1,278 @@
+//! Distance estimated [fractals][0].
+//!
+//! Their distance functions are only lower bounds of the real distance, which
+//! is all sphere tracing needs. Besides the distance, each fractal records
+//! how close the orbit of a point gets to the origin while iterating, the
+//! so called [orbit trap][1], which is a nice way to color them.
+//!
+//! [0]: https://iquilezles.org/articles/distancefractals/
+//! [1]: https://en.wikipedia.org/wiki/Orbit_trap
+
+use geo::{Aabb, Vec3};
+
+use super::{Cube, SignedDistanceFunction};
+
+/// The radius after which the orbit of a point is considered to escape. The
+/// `Mandelbulb` raises it for powers below 2 whose bounding sphere is larger.
+const BAILOUT: f64 = 2.0;
+
+/// The 3D version of the Mandelbrot set found by Daniel White and Paul
+/// Nylander that iterates `z = z^power + p` in spherical coordinates.
+#[derive(Debug, Clone)]
+pub struct Mandelbulb {
+    power: f64,
+    iterations: u32,
+    bailout: f64,
+}
+
+impl Mandelbulb {
+    /// Create a `Mandelbulb` of the given power, 8 is the classic one. More
+    /// iterations give more details at the cost of slower distance
+    /// evaluations.
+    pub fn new(power: f64, iterations: u32) -> Self {
+        assert!(power > 1.0);
+
+        Mandelbulb {
+            power,
+            iterations,
+            bailout: BAILOUT.max(escape_radius(power)),
+        }
+    }
+
+    /// Return the distance estimate and the orbit trap of `p`.
+    fn orbit(&self, p: &Vec3) -> (f64, f64) {
+        let mut z = *p;
+        let mut dr = 1.0;
+        let mut r = z.norm();
+        let mut trap = f64::INFINITY;
+
+        for _ in 0..self.iterations {
+            if r > self.bailout {
+                break;
+            }
+
+            let theta = if r == 0.0 { 0.0 } else { (z.z / r).acos() };
+            let phi = z.y.atan2(z.x);
+
+            dr = self.power * r.powf(self.power - 1.0) * dr + 1.0;
+
+            let (st, ct) = (theta * self.power).sin_cos();
+            let (sp, cp) = (phi * self.power).sin_cos();
+            z = Vec3::new(st * cp, st * sp, ct) * r.powf(self.power) + *p;
+
+            r = z.norm();
+            trap = trap.min(r);
+        }
+
+        if r == 0.0 {
+            return (0.0, 0.0);
+        }
+
+        (0.5 * r.ln() * r / dr, trap.min(1.0))
+    }
+}
+
+impl SignedDistanceFunction for Mandelbulb {
+    fn bbox(&self) -> Aabb {
+        Aabb::cube(Vec3::zero(), 2.0 * escape_radius(self.power))
+    }
+
+    fn dist(&self, p: &Vec3) -> f64 {
+        self.orbit(p).0
+    }
+
+    fn orbit_trap(&self, p: &Vec3) -> Option<f64> {
+        Some(self.orbit(p).1)
+    }
+}
+
+/// Return the radius of the sphere outside of which the orbits of a
+/// `Mandelbulb` of the given power escape, since there |z^power + p| > |p|.
+fn escape_radius(power: f64) -> f64 {
+    2.0_f64.powf(1.0 / (power - 1.0))
+}
+
+/// The Menger sponge inside the `[-1, 1]` cube, at each iteration the middle
+/// of every face and the center of the cubes are carved out.
+#[derive(Debug, Clone)]
+pub struct MengerSponge {
+    iterations: u32,
+}
+
+impl MengerSponge {
+    pub fn new(iterations: u32) -> Self {
+        MengerSponge { iterations }
+    }
+
+    /// Return the distance and the orbit trap of `p`.
+    fn orbit(&self, p: &Vec3) -> (f64, f64) {
+        let mut d = Cube::new(Vec3::replicate(2.0)).dist(p);
+        let mut s = 1.0;
+        let mut trap = f64::INFINITY;
+
+        for _ in 0..self.iterations {
+            // position inside the cell of the current level, in [-1, 1]
+            let a = Vec3::new(
+                (p.x * s).rem_euclid(2.0) - 1.0,
+                (p.y * s).rem_euclid(2.0) - 1.0,
+                (p.z * s).rem_euclid(2.0) - 1.0,
+            );
+            s *= 3.0;
+            trap = trap.min(a.norm());
+
+            // distance from the three crossing bars that are carved out
+            let r = Vec3::new(
+                (1.0 - 3.0 * a.x.abs()).abs(),
+                (1.0 - 3.0 * a.y.abs()).abs(),
+                (1.0 - 3.0 * a.z.abs()).abs(),
+            );
+            let da = r.x.max(r.y);
+            let db = r.y.max(r.z);
+            let dc = r.z.max(r.x);
+
+            d = d.max((da.min(db).min(dc) - 1.0) / s);
+        }
+
+        (d, trap.min(1.0))
+    }
+}
+
+impl SignedDistanceFunction for MengerSponge {
+    fn bbox(&self) -> Aabb {
+        Aabb::cube(Vec3::zero(), 2.0)
+    }
+
+    fn dist(&self, p: &Vec3) -> f64 {
+        self.orbit(p).0
+    }
+
+    fn orbit_trap(&self, p: &Vec3) -> Option<f64> {
+        Some(self.orbit(p).1)
+    }
+}
+
+/// The Sierpinski tetrahedron whose vertices are `(1, 1, 1)`, `(-1, -1, 1)`,
+/// `(1, -1, -1)` and `(-1, 1, -1)`. At each iteration the tetrahedron is
+/// replaced by four copies half its size, one at each vertex.
+#[derive(Debug, Clone)]
+pub struct SierpinskiTetrahedron {
+    iterations: u32,
+}
+
+impl SierpinskiTetrahedron {
+    pub fn new(iterations: u32) -> Self {
+        SierpinskiTetrahedron { iterations }
+    }
+
+    /// Return the distance estimate and the orbit trap of `p`.
+    fn orbit(&self, p: &Vec3) -> (f64, f64) {
+        let mut z = *p;
+        let mut trap = f64::INFINITY;
+
+        for _ in 0..self.iterations {
+            // fold the space across the planes between the (1, 1, 1) vertex
+            // and the others so that only its copy has to be considered
+            if z.x + z.y < 0.0 {
+                (z.x, z.y) = (-z.y, -z.x);
+            }
+            if z.x + z.z < 0.0 {
+                (z.x, z.z) = (-z.z, -z.x);
+            }
+            if z.y + z.z < 0.0 {
+                (z.y, z.z) = (-z.z, -z.y);
+            }
+
+            z = z * 2.0 - Vec3::replicate(1.0);
+            trap = trap.min(z.norm());
+        }
+
+        let faces = (-z.x - z.y - z.z)
+            .max(z.x + z.y - z.z)
+            .max(-z.x + z.y + z.z)
+            .max(z.x - z.y + z.z);
+        let d = (faces - 1.0) / 3.0_f64.sqrt();
+
+        (d * 0.5_f64.powi(self.iterations as i32), trap.min(1.0))
+    }
+}
+
+impl SignedDistanceFunction for SierpinskiTetrahedron {
+    fn bbox(&self) -> Aabb {
+        Aabb::cube(Vec3::zero(), 2.0)
+    }
+
+    fn dist(&self, p: &Vec3) -> f64 {
+        self.orbit(p).0
+    }
+
+    fn orbit_trap(&self, p: &Vec3) -> Option<f64> {
+        Some(self.orbit(p).1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distance_estimates() {
+        // the estimates never exceed the distance from the bounding box
+        let p = Vec3::new(3.0, 0.0, 0.0);
+        assert!(Mandelbulb::new(8.0, 10).dist(&p) <= 3.0 - 1.0);
+        assert!(MengerSponge::new(3).dist(&p) <= 2.0);
+        assert!(SierpinskiTetrahedron::new(6).dist(&p) <= 2.0);
+
+        // the center of the sponge and the middle of its faces are carved out
+        let menger = MengerSponge::new(3);
+        assert!(menger.dist(&Vec3::zero()) > 0.0);
+        assert!(menger.dist(&Vec3::new(0.0, 0.0, 0.99)) > 0.0);
+        assert!(menger.dist(&Vec3::new(0.99, 0.99, 0.99)) < 0.0);
+
+        // the vertices of the tetrahedron are kept at all the levels, but not
+        // its center
+        let sierpinski = SierpinskiTetrahedron::new(6);
+        assert!(sierpinski.dist(&Vec3::new(0.99, 0.99, 0.99)) < 0.0);
+        assert!(sierpinski.dist(&Vec3::zero()) > 0.0);
+
+        let mandelbulb = Mandelbulb::new(8.0, 10);
+        assert!(mandelbulb.dist(&Vec3::new(0.0, 0.0, 0.5)) < 1e-3);
+        assert!(mandelbulb.dist(&Vec3::new(0.0, 0.0, 1.5)) > 0.0);
+    }
+
+    #[test]
+    fn test_low_power_mandelbulb() {
+        // the bounding box extends up to 4 and the points farther than 2 from
+        // the origin still need to be iterated, otherwise the estimate drops
+        // right after 2
+        let mandelbulb = Mandelbulb::new(1.5, 10);
+        assert_eq!(mandelbulb.bbox(), Aabb::cube(Vec3::zero(), 8.0));
+
+        for dir in [Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 0.6, 0.8)] {
+            let dists = [1.99, 2.01, 3.0, 3.99]
+                .iter()
+                .map(|&r| mandelbulb.dist(&(dir * r)))
+                .collect::<Vec<_>>();
+
+            assert!(dists[0] > 0.0);
+            assert!(dists.windows(2).all(|w| w[0] < w[1]), "{:?}", dists);
+        }
+    }
+
+    #[test]
+    fn test_orbit_traps() {
+        for p in [
+            Vec3::zero(),
+            Vec3::new(0.3, -0.2, 0.5),
+            Vec3::replicate(5.0),
+        ] {
+            for trap in [
+                Mandelbulb::new(8.0, 10).orbit_trap(&p),
+                MengerSponge::new(3).orbit_trap(&p),
+                SierpinskiTetrahedron::new(6).orbit_trap(&p),
+            ] {
+                let trap = trap.unwrap();
+                assert!((0.0..=1.0).contains(&trap), "{:?} {}", p, trap);
+            }
+        }
+    }
+}
@@ -0,0 +1,2149 @@
+//! Simple [Constructive Solid Geometry][0] framework.
+//!
+//! [0]: https://en.wikipedia.org/wiki/Constructive_solid_geometry
+//!
+//! The distance functions are based on
+//! http://www.iquilezles.org/www/articles/distfunctions/distfunctions.htm
+//!
+//! Shapes can either be built at compile time by composing the generic types
+//! or at runtime as `BoxedSdf`s, for example by parsing the textual format
+//! described in `sexpr`.
+
+pub mod fractal;
+pub mod sexpr;
+
+pub use fractal::{Mandelbulb, MengerSponge, SierpinskiTetrahedron};
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use geo::{
+    mat4::{Mat4, Transform},
+    ray::Ray,
+    spatial_index::Shape,
+    Aabb, Axis, Vec3,
+};
+
+use crate::{Hit, Material, Surface};
+
+/// A geometry whose surface is the zero iso-surface of a
+/// `SignedDistanceFunction`, rendered with [sphere tracing][0].
+///
+/// The rays take over-relaxed steps as described in "Enhanced Sphere Tracing"
+/// by Keinert et al. that fall back to plain steps when they overshoot. A ray
+/// hits the surface when the distance is smaller than `epsilon` times the
+/// distance travelled so that far away surfaces don't waste steps on details
+/// that are smaller than a pixel.
+///
+/// [0]: https://en.wikipedia.org/wiki/Ray_marching
+#[derive(Debug)]
+pub struct SdfGeometry<S> {
+    sdf: S,
+    bbox: Aabb,
+    relaxation: f64,
+    epsilon: f64,
+    max_steps: u32,
+    stats: Option<MarchCounters>,
+}
+
+/// Statistics about the sphere tracing of a `SdfGeometry`, useful to tune its
+/// parameters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MarchStats {
+    /// Number of rays that were marched inside the bounding box.
+    pub rays: u64,
+
+    /// Number of rays that hit the surface.
+    pub hits: u64,
+
+    /// Total number of steps, that is evaluations of the distance function.
+    pub steps: u64,
+
+    /// Number of rays that gave up after `max_steps`.
+    pub exhausted: u64,
+
+    /// Number of over-relaxed steps that went too far and had to be undone.
+    pub relaxation_failures: u64,
+}
+
+#[derive(Debug, Default)]
+struct MarchCounters {
+    rays: AtomicU64,
+    hits: AtomicU64,
+    steps: AtomicU64,
+    exhausted: AtomicU64,
+    relaxation_failures: AtomicU64,
+}
+
+impl<S: SignedDistanceFunction> SdfGeometry<S> {
+    pub fn new(sdf: S) -> Self {
+        SdfGeometry {
+            bbox: sdf.bbox(),
+            sdf,
+            relaxation: 1.5,
+            epsilon: 1e-5,
+            max_steps: 1000,
+            stats: None,
+        }
+    }
+
+    /// Set how much the steps are stretched wrt the distance, it must be in
+    /// `[1, 2)`. Bigger values take fewer steps far from the surface but waste
+    /// more of them when overshooting. `1` disables over-relaxation.
+    pub fn with_relaxation(mut self, relaxation: f64) -> Self {
+        assert!((1.0..2.0).contains(&relaxation));
+        self.relaxation = relaxation;
+        self
+    }
+
+    /// Set the tolerance of the hits relative to the distance travelled by the
+    /// ray.
+    pub fn with_epsilon(mut self, epsilon: f64) -> Self {
+        self.epsilon = epsilon;
+        self
+    }
+
+    /// Set the maximum number of steps after which a ray is considered to have
+    /// missed the surface.
+    pub fn with_max_steps(mut self, max_steps: u32) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Collect the `MarchStats` of all the rays. It's off by default because
+    /// updating the counters from all the threads slows down the rendering.
+    pub fn with_stats(mut self) -> Self {
+        self.stats = Some(MarchCounters::default());
+        self
+    }
+
+    /// Return the `MarchStats` collected so far, if enabled by `with_stats`.
+    pub fn stats(&self) -> Option<MarchStats> {
+        let stats = self.stats.as_ref()?;
+
+        Some(MarchStats {
+            rays: stats.rays.load(Ordering::Relaxed),
+            hits: stats.hits.load(Ordering::Relaxed),
+            steps: stats.steps.load(Ordering::Relaxed),
+            exhausted: stats.exhausted.load(Ordering::Relaxed),
+            relaxation_failures: stats.relaxation_failures.load(Ordering::Relaxed),
+        })
+    }
+
+    /// Sphere trace the ray and return the t of the hit, if any.
+    fn march(&self, ray: &Ray, stats: &mut MarchStats) -> Option<f64> {
+        let (t1, t2) = self.bbox.ray_intersection(ray)?;
+        if t2 < t1 || t2 < 0.0 {
+            return None;
+        }
+
+        stats.rays += 1;
+
+        // all the distances are in world units, hence they must be scaled wrt
+        // the length of the direction to get t parameters
+        let dir_norm = ray.dir.norm();
+        let threshold = |t: f64| self.epsilon * (1.0 + t * dir_norm);
+        let dist = |t: f64| self.sdf.dist(&ray.point_at(t));
+
+        let mut t = t1.max(0.0);
+        let mut d = dist(t);
+        stats.steps += 1;
+
+        // rays that start on the surface, like the secondary rays, must leave
+        // it first otherwise they would hit it right away. Then they march
+        // towards the surface either from outside or from inside.
+        if t1 <= 0.0 {
+            let mut step = threshold(t);
+            while d.abs() < threshold(t) {
+                t += step / dir_norm;
+                step *= 2.0;
+                if t > t2 {
+                    return None;
+                }
+
+                d = dist(t);
+                stats.steps += 1;
+            }
+        }
+        let sign = d.signum();
+
+        let mut relaxation = self.relaxation;
+        let (mut prev_radius, mut prev_step) = (0.0, 0.0);
+
+        for _ in 0..self.max_steps {
+            let signed_radius = sign * d;
+            let radius = signed_radius.abs();
+
+            // the unbounding spheres of the last two steps don't overlap, hence
+            // the surface might have been skipped. Go back to where a plain step
+            // would have ended and stop relaxing.
+            if relaxation > 1.0 && (signed_radius < 0.0 || radius + prev_radius < prev_step) {
+                stats.relaxation_failures += 1;
+
+                t -= (prev_step - prev_radius) / dir_norm;
+                relaxation = 1.0;
+                prev_step = 0.0;
+                d = dist(t);
+                stats.steps += 1;
+                continue;
+            }
+
+            if signed_radius < threshold(t) {
+                stats.hits += 1;
+                return Some(t);
+            }
+
+            let step = signed_radius * relaxation;
+            t += step / dir_norm;
+            if t > t2 {
+                return None;
+            }
+
+            prev_radius = radius;
+            prev_step = step;
+            d = dist(t);
+            stats.steps += 1;
+        }
+
+        stats.exhausted += 1;
+        None
+    }
+
+    /// Calculate the normal at `p` from the analytic gradient of the distance
+    /// function or, if it's not available, with the tetrahedron technique which
+    /// needs only four evaluations of the distance at `h` from `p`.
+    fn normal(&self, p: Vec3, h: f64) -> Vec3 {
+        if let Some(g) = self.sdf.gradient(&p) {
+            if g.norm2() > 0.0 {
+                return g.normalized();
+            }
+        }
+
+        [
+            Vec3::new(1.0, -1.0, -1.0),
+            Vec3::new(-1.0, -1.0, 1.0),
+            Vec3::new(-1.0, 1.0, -1.0),
+            Vec3::new(1.0, 1.0, 1.0),
+        ]
+        .iter()
+        .map(|&k| k * self.sdf.dist(&(p + k * h)))
+        .sum::<Vec3>()
+        .normalized()
+    }
+}
+
+pub trait SignedDistanceFunction: Sized + std::fmt::Debug {
+    fn dist(&self, p: &Vec3) -> f64;
+    fn bbox(&self) -> Aabb;
+
+    /// The gradient of the distance at `p`, if it can be calculated
+    /// analytically. It doesn't need to be normalized. When `None` it's
+    /// estimated numerically.
+    fn gradient(&self, _p: &Vec3) -> Option<Vec3> {
+        None
+    }
+
+    /// The orbit trap of `p` in `[0, 1]` for the fractals, that is how close
+    /// its orbit gets to the origin. It's reported in the `Hit`s so that the
+    /// materials can use it for coloring.
+    fn orbit_trap(&self, _p: &Vec3) -> Option<f64> {
+        None
+    }
+
+    /// The `Material` of the leaf that dominates at `p`, if it has one. It's
+    /// reported in the `Hit`s and takes the place of the material of the
+    /// whole object.
+    fn material_at(&self, _p: &Vec3) -> Option<&Material> {
+        None
+    }
+
+    /// Give this shape its own `Material`, for example to color the holes of
+    /// a `difference` differently. Materials set deeper in the tree win.
+    ///
+    /// A light `Material` set this way makes the shape glow, but it's not
+    /// sampled for direct lighting, see `Scene::lights`.
+    fn with_material(self, material: Material) -> WithMaterial<Self> {
+        WithMaterial {
+            sdf: self,
+            material,
+        }
+    }
+
+    fn transformed(self, xform: Mat4) -> Transformed<Self> {
+        let inverse_matrix = xform.inverse();
+        Transformed {
+            sdf: self,
+            matrix: xform,
+            inverse_matrix,
+        }
+    }
+
+    fn union<S: SignedDistanceFunction>(self, other: S) -> Union<Self, S> {
+        Union {
+            left: self,
+            right: other,
+        }
+    }
+
+    fn intersection<S: SignedDistanceFunction>(self, other: S) -> Intersection<Self, S> {
+        Intersection {
+            left: self,
+            right: other,
+        }
+    }
+
+    fn difference<S: SignedDistanceFunction>(self, other: S) -> Difference<Self, S> {
+        Difference {
+            left: self,
+            right: other,
+        }
+    }
+
+    /// Like `union`, but the surfaces are smoothly blended where they meet.
+    fn smooth_union<S: SignedDistanceFunction>(
+        self,
+        other: S,
+        blend: Blend,
+    ) -> SmoothUnion<Self, S> {
+        SmoothUnion {
+            left: self,
+            right: other,
+            blend,
+        }
+    }
+
+    /// Like `intersection`, but the edges where the surfaces meet are
+    /// smoothly rounded.
+    fn smooth_intersection<S: SignedDistanceFunction>(
+        self,
+        other: S,
+        blend: Blend,
+    ) -> SmoothIntersection<Self, S> {
+        SmoothIntersection {
+            left: self,
+            right: other,
+            blend,
+        }
+    }
+
+    /// Like `difference`, but the edges of the carved shape are smoothly
+    /// rounded.
+    fn smooth_difference<S: SignedDistanceFunction>(
+        self,
+        other: S,
+        blend: Blend,
+    ) -> SmoothDifference<Self, S> {
+        SmoothDifference {
+            left: self,
+            right: other,
+            blend,
+        }
+    }
+
+    /// Repeat the shape `counts` times along each axis every `spacing`. The
+    /// copies start at the origin and go towards `spacing`. The shape must
+    /// fit in a single cell of size `spacing` centered at the origin
+    /// otherwise the distance is wrong.
+    fn repeated(self, spacing: Vec3, counts: [u32; 3]) -> Repeated<Self> {
+        Repeated {
+            sdf: self,
+            spacing,
+            counts,
+        }
+    }
+
+    /// Repeat the shape every `spacing` in all the directions filling
+    /// `bounds`, the copies that cross the bounds are cut. A spacing of 0 along
+    /// an axis disables the repetition along it.
+    fn repeated_within(self, spacing: Vec3, bounds: Aabb) -> RepeatedWithin<Self> {
+        RepeatedWithin {
+            sdf: self,
+            spacing,
+            bounds,
+        }
+    }
+
+    /// Replace the negative side of the shape wrt the given axis with the
+    /// mirror image of the positive one.
+    fn mirrored(self, axis: Axis) -> Mirrored<Self> {
+        Mirrored { sdf: self, axis }
+    }
+
+    /// Twist the shape around the Y axis by `rate` radians per unit of height.
+    fn twisted(self, rate: f64) -> Twisted<Self> {
+        let stretch = 1.0 + rate.abs() * max_radius(&self.bbox(), Axis::Y);
+
+        Twisted {
+            sdf: self,
+            rate,
+            stretch,
+        }
+    }
+
+    /// Bend the shape in the XY plane by rotating it around the Z axis by
+    /// `rate` radians per unit along X.
+    fn bent(self, rate: f64) -> Bent<Self> {
+        let stretch = 1.0 + rate.abs() * max_radius(&self.bbox(), Axis::Z);
+
+        Bent {
+            sdf: self,
+            rate,
+            stretch,
+        }
+    }
+
+    /// Stretch the shape by splitting it at the origin and moving the halves
+    /// apart by `amount` on each side, filling the gap with the section at
+    /// the origin.
+    fn elongated(self, amount: Vec3) -> Elongated<Self> {
+        Elongated { sdf: self, amount }
+    }
+
+    /// Grow the shape by `radius` in all the directions rounding its edges.
+    fn rounded(self, radius: f64) -> Rounded<Self> {
+        Rounded { sdf: self, radius }
+    }
+
+    /// Turn the shape into a shell of the given thickness around its surface.
+    fn onion(self, thickness: f64) -> Onion<Self> {
+        Onion {
+            sdf: self,
+            thickness,
+        }
+    }
+
+    /// Move the surface outwards by the amount returned by `displacement` at
+    /// each point. `amplitude` must be the maximum absolute value returned by
+    /// `displacement` and `lipschitz` an upper bound of how fast it changes,
+    /// the latter is used to shorten the ray marching steps so that they're
+    /// still safe.
+    fn displaced<F>(self, displacement: F, amplitude: f64, lipschitz: f64) -> Displaced<Self, F>
+    where
+        F: Fn(&Vec3) -> f64,
+    {
+        Displaced {
+            sdf: self,
+            displacement,
+            amplitude: amplitude.abs(),
+            lipschitz: lipschitz.abs(),
+        }
+    }
+}
+
+/// Object safe version of `SignedDistanceFunction` implemented by all the
+/// thread safe `SignedDistanceFunction`s. It allows to build shapes whose
+/// structure is only known at runtime.
+pub trait DynSignedDistanceFunction: std::fmt::Debug + Send + Sync {
+    fn dyn_dist(&self, p: &Vec3) -> f64;
+    fn dyn_bbox(&self) -> Aabb;
+    fn dyn_gradient(&self, p: &Vec3) -> Option<Vec3>;
+    fn dyn_orbit_trap(&self, p: &Vec3) -> Option<f64>;
+    fn dyn_material_at(&self, p: &Vec3) -> Option<&Material>;
+}
+
+/// A `SignedDistanceFunction` whose concrete type has been erased.
+pub type BoxedSdf = Box<dyn DynSignedDistanceFunction>;
+
+impl<S: SignedDistanceFunction + Send + Sync> DynSignedDistanceFunction for S {
+    fn dyn_dist(&self, p: &Vec3) -> f64 {
+        self.dist(p)
+    }
+
+    fn dyn_bbox(&self) -> Aabb {
+        self.bbox()
+    }
+
+    fn dyn_gradient(&self, p: &Vec3) -> Option<Vec3> {
+        self.gradient(p)
+    }
+
+    fn dyn_orbit_trap(&self, p: &Vec3) -> Option<f64> {
+        self.orbit_trap(p)
+    }
+
+    fn dyn_material_at(&self, p: &Vec3) -> Option<&Material> {
+        self.material_at(p)
+    }
+}
+
+impl SignedDistanceFunction for BoxedSdf {
+    fn dist(&self, p: &Vec3) -> f64 {
+        self.as_ref().dyn_dist(p)
+    }
+
+    fn bbox(&self) -> Aabb {
+        self.as_ref().dyn_bbox()
+    }
+
+    fn gradient(&self, p: &Vec3) -> Option<Vec3> {
+        self.as_ref().dyn_gradient(p)
+    }
+
+    fn orbit_trap(&self, p: &Vec3) -> Option<f64> {
+        self.as_ref().dyn_orbit_trap(p)
+    }
+
+    fn material_at(&self, p: &Vec3) -> Option<&Material> {
+        self.as_ref().dyn_material_at(p)
+    }
+}
+
+impl<S: SignedDistanceFunction> Surface for SdfGeometry<S> {
+    fn normal_at(&self, p: Vec3) -> Vec3 {
+        self.normal(p, self.epsilon * (1.0 + p.norm()))
+    }
+}
+
+impl<S: SignedDistanceFunction> Shape for SdfGeometry<S> {
+    type Intersection = Hit;
+
+    fn bbox(&self) -> Aabb {
+        self.bbox.clone()
+    }
+
+    fn intersection(&self, ray: &Ray) -> Option<Self::Intersection> {
+        let mut stats = MarchStats::default();
+        let t = self.march(ray, &mut stats);
+
+        if let Some(counters) = &self.stats {
+            counters.rays.fetch_add(stats.rays, Ordering::Relaxed);
+            counters.hits.fetch_add(stats.hits, Ordering::Relaxed);
+            counters.steps.fetch_add(stats.steps, Ordering::Relaxed);
+            counters
+                .exhausted
+                .fetch_add(stats.exhausted, Ordering::Relaxed);
+            counters
+                .relaxation_failures
+                .fetch_add(stats.relaxation_failures, Ordering::Relaxed);
+        }
+
+        // the normal is calculated here where the tolerance of the hit is known
+        let t = t?;
+        let p = ray.point_at(t);
+        let h = self.epsilon * (1.0 + t * ray.dir.norm());
+
+        let mut hit = Hit::new(t, Some((p, self.normal(p, h))));
+        hit.orbit_trap = self.sdf.orbit_trap(&p);
+        hit.material = self.sdf.material_at(&p).cloned();
+
+        Some(hit)
+    }
+}
+
+impl MarchStats {
+    /// The average number of steps per marched ray.
+    pub fn average_steps(&self) -> f64 {
+        if self.rays == 0 {
+            0.0
+        } else {
+            self.steps as f64 / self.rays as f64
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Sphere {
+    radius: f64,
+}
+
+impl Sphere {
+    pub fn new(r: f64) -> Self {
+        Sphere { radius: r }
+    }
+}
+
+impl SignedDistanceFunction for Sphere {
+    fn bbox(&self) -> Aabb {
+        geo::sphere::bounding_box(Vec3::zero(), self.radius)
+    }
+
+    fn dist(&self, p: &Vec3) -> f64 {
+        p.norm() - self.radius
+    }
+
+    fn gradient(&self, p: &Vec3) -> Option<Vec3> {
+        Some(*p)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Cube {
+    size: Vec3,
+}
+
+impl Cube {
+    pub fn new(size: Vec3) -> Self {
+        Cube { size }
+    }
+}
+
+impl SignedDistanceFunction for Cube {
+    fn bbox(&self) -> Aabb {
+        let d = self.size / 2.0;
+        Aabb::new(-d).expanded(d)
+    }
+
+    fn dist(&self, p: &Vec3) -> f64 {
+        let mut x = if p.x < 0.0 { -p.x } else { p.x };
+        let mut y = if p.y < 0.0 { -p.y } else { p.y };
+        let mut z = if p.z < 0.0 { -p.z } else { p.z };
+
+        x -= self.size.x / 2.0;
+        y -= self.size.y / 2.0;
+        z -= self.size.z / 2.0;
+
+        let a = x.max(y).max(z).min(0.0);
+
+        x = x.max(0.0);
+        y = y.max(0.0);
+        z = z.max(0.0);
+
+        let b = (x.powi(2) + y.powi(2) + z.powi(2)).sqrt();
+        a + b
+    }
+
+    fn gradient(&self, p: &Vec3) -> Option<Vec3> {
+        let sign = Vec3::new(p.x.signum(), p.y.signum(), p.z.signum());
+        let w = Vec3::new(p.x.abs(), p.y.abs(), p.z.abs()) - self.size / 2.0;
+
+        // outside the gradient points away from the closest point on the
+        // surface, inside it's the normal of the closest face
+        let outside = Vec3::new(w.x.max(0.0), w.y.max(0.0), w.z.max(0.0));
+        let g = if outside.norm2() > 0.0 {
+            outside
+        } else if w.x >= w.y && w.x >= w.z {
+            Vec3::new(1.0, 0.0, 0.0)
+        } else if w.y >= w.z {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(0.0, 0.0, 1.0)
+        };
+
+        Some(g * sign)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Cylinder {
+    radius: f64,
+    height: f64,
+}
+
+impl Cylinder {
+    pub fn new(radius: f64, height: f64) -> Self {
+        Cylinder { radius, height }
+    }
+}
+
+impl SignedDistanceFunction for Cylinder {
+    fn bbox(&self) -> Aabb {
+        Aabb::new(Vec3::new(-self.radius, -self.height / 2.0, -self.radius)).expanded(Vec3::new(
+            self.radius,
+            self.height / 2.0,
+            self.radius,
+        ))
+    }
+
+    fn dist(&self, p: &Vec3) -> f64 {
+        let mut x = (p.x.powi(2) + p.z.powi(2)).sqrt();
+        if x < 0.0 {
+            x = -x;
+        }
+
+        let mut y = if p.y < 0.0 { -p.y } else { p.y };
+
+        x -= self.radius;
+        y -= self.height / 2.0;
+        let a = x.max(y).min(0.0);
+
+        x = x.max(0.0);
+        y = y.max(0.0);
+        let b = (x.powi(2) + y.powi(2)).sqrt();
+
+        a + b
+    }
+
+    fn gradient(&self, p: &Vec3) -> Option<Vec3> {
+        let radial = Vec3::new(p.x, 0.0, p.z);
+        if radial.norm2() == 0.0 {
+            return None;
+        }
+
+        let radial = radial.normalized();
+        let axial = Vec3::new(0.0, p.y.signum(), 0.0);
+
+        let x = (p.x.powi(2) + p.z.powi(2)).sqrt() - self.radius;
+        let y = p.y.abs() - self.height / 2.0;
+
+        let g = if x > 0.0 || y > 0.0 {
+            radial * x.max(0.0) + axial * y.max(0.0)
+        } else if x > y {
+            radial
+        } else {
+            axial
+        };
+
+        Some(g)
+    }
+}
+
+/// Torus lying on the XZ plane, consistently with `Cylinder` whose axis is Y.
+#[derive(Debug, Clone)]
+pub struct Torus {
+    major_radius: f64,
+    minor_radius: f64,
+}
+
+impl Torus {
+    pub fn new(major_radius: f64, minor_radius: f64) -> Self {
+        Torus {
+            major_radius,
+            minor_radius,
+        }
+    }
+}
+
+impl SignedDistanceFunction for Torus {
+    fn bbox(&self) -> Aabb {
+        let r = self.major_radius + self.minor_radius;
+        Aabb::new(Vec3::new(-r, -self.minor_radius, -r)).expanded(Vec3::new(
+            r,
+            self.minor_radius,
+            r,
+        ))
+    }
+
+    fn dist(&self, p: &Vec3) -> f64 {
+        let x = (p.x.powi(2) + p.z.powi(2)).sqrt() - self.major_radius;
+        (x.powi(2) + p.y.powi(2)).sqrt() - self.minor_radius
+    }
+
+    fn gradient(&self, p: &Vec3) -> Option<Vec3> {
+        let radial = Vec3::new(p.x, 0.0, p.z);
+        if radial.norm2() == 0.0 {
+            return None;
+        }
+
+        // away from the closest point of the central circle
+        Some(*p - radial.normalized() * self.major_radius)
+    }
+}
+
+/// Segment from `a` to `b` swept by a sphere of the given radius.
+#[derive(Debug, Clone)]
+pub struct Capsule {
+    a: Vec3,
+    b: Vec3,
+    radius: f64,
+}
+
+impl Capsule {
+    pub fn new(a: Vec3, b: Vec3, radius: f64) -> Self {
+        Capsule { a, b, radius }
+    }
+}
+
+impl SignedDistanceFunction for Capsule {
+    fn bbox(&self) -> Aabb {
+        geo::sphere::bounding_box(self.a, self.radius)
+            .union(&geo::sphere::bounding_box(self.b, self.radius))
+    }
+
+    fn dist(&self, p: &Vec3) -> f64 {
+        p.segment_dist(self.a, self.b) - self.radius
+    }
+
+    fn gradient(&self, p: &Vec3) -> Option<Vec3> {
+        let ab = self.b - self.a;
+        let s = ((*p - self.a).dot(ab) / ab.norm2()).clamp(0.0, 1.0);
+
+        Some(*p - (self.a + ab * s))
+    }
+}
+
+/// Cone frustum centered at the origin going through the Y axis. A proper cone
+/// can be created by using a radius of 0 for the top.
+#[derive(Debug, Clone)]
+pub struct Cone {
+    height: f64,
+    bottom_radius: f64,
+    top_radius: f64,
+}
+
+impl Cone {
+    pub fn new(height: f64, bottom_radius: f64, top_radius: f64) -> Self {
+        Cone {
+            height,
+            bottom_radius,
+            top_radius,
+        }
+    }
+}
+
+impl SignedDistanceFunction for Cone {
+    fn bbox(&self) -> Aabb {
+        let r = self.bottom_radius.max(self.top_radius);
+        Aabb::new(Vec3::new(-r, -self.height / 2.0, -r)).expanded(Vec3::new(
+            r,
+            self.height / 2.0,
+            r,
+        ))
+    }
+
+    fn dist(&self, p: &Vec3) -> f64 {
+        let h = self.height / 2.0;
+        let (r1, r2) = (self.bottom_radius, self.top_radius);
+
+        // work in 2D on the plane that contains the axis and p
+        let (qx, qy) = ((p.x.powi(2) + p.z.powi(2)).sqrt(), p.y);
+        let (k1x, k1y) = (r2, h);
+        let (k2x, k2y) = (r2 - r1, 2.0 * h);
+
+        // distance from the caps
+        let cax = qx - qx.min(if qy < 0.0 { r1 } else { r2 });
+        let cay = qy.abs() - h;
+
+        // distance from the side
+        let s =
+            (((k1x - qx) * k2x + (k1y - qy) * k2y) / (k2x.powi(2) + k2y.powi(2))).clamp(0.0, 1.0);
+        let cbx = qx - k1x + k2x * s;
+        let cby = qy - k1y + k2y * s;
+
+        let sign = if cbx < 0.0 && cay < 0.0 { -1.0 } else { 1.0 };
+        sign * (cax.powi(2) + cay.powi(2))
+            .min(cbx.powi(2) + cby.powi(2))
+            .sqrt()
+    }
+}
+
+/// Box centered at the origin with the given size whose edges are rounded
+/// with the given radius.
+#[derive(Debug, Clone)]
+pub struct RoundedBox {
+    size: Vec3,
+    radius: f64,
+}
+
+impl RoundedBox {
+    pub fn new(size: Vec3, radius: f64) -> Self {
+        RoundedBox { size, radius }
+    }
+}
+
+impl SignedDistanceFunction for RoundedBox {
+    fn bbox(&self) -> Aabb {
+        let d = self.size / 2.0;
+        Aabb::new(-d).expanded(d)
+    }
+
+    fn dist(&self, p: &Vec3) -> f64 {
+        let half = self.size / 2.0 - self.radius;
+        let q = Vec3::new(p.x.abs() - half.x, p.y.abs() - half.y, p.z.abs() - half.z);
+
+        let outside = Vec3::new(q.x.max(0.0), q.y.max(0.0), q.z.max(0.0)).norm();
+        let inside = q.x.max(q.y).max(q.z).min(0.0);
+
+        outside + inside - self.radius
+    }
+}
+
+/// Prism with a regular hexagonal base centered at the origin going through
+/// the Y axis. `radius` is the distance between the center of the hexagon and
+/// its sides.
+#[derive(Debug, Clone)]
+pub struct HexagonalPrism {
+    radius: f64,
+    height: f64,
+}
+
+impl HexagonalPrism {
+    pub fn new(radius: f64, height: f64) -> Self {
+        HexagonalPrism { radius, height }
+    }
+}
+
+impl SignedDistanceFunction for HexagonalPrism {
+    fn bbox(&self) -> Aabb {
+        // the distance between the center and the vertices of the hexagon
+        let r = self.radius * 2.0 / 3.0_f64.sqrt();
+        Aabb::new(Vec3::new(-r, -self.height / 2.0, -r)).expanded(Vec3::new(
+            r,
+            self.height / 2.0,
+            r,
+        ))
+    }
+
+    fn dist(&self, p: &Vec3) -> f64 {
+        const K: (f64, f64, f64) = (-0.866_025_403_784_438_6, 0.5, 0.577_350_269_189_625_8);
+
+        let (mut x, mut y, z) = (p.x.abs(), p.z.abs(), p.y.abs());
+
+        // fold the point in the first sixth of the hexagon
+        let d = 2.0 * (K.0 * x + K.1 * y).min(0.0);
+        x -= d * K.0;
+        y -= d * K.1;
+
+        let cx = x.clamp(-K.2 * self.radius, K.2 * self.radius);
+        let dx = ((x - cx).powi(2) + (y - self.radius).powi(2)).sqrt() * (y - self.radius).signum();
+        let dy = z - self.height / 2.0;
+
+        dx.max(dy).min(0.0) + (dx.max(0.0).powi(2) + dy.max(0.0).powi(2)).sqrt()
+    }
+}
+
+/// Prism with an equilateral triangle base centered at the origin going
+/// through the Y axis. `radius` is the distance between the center of the
+/// triangle and its vertices, one of which lies on the positive Z axis. The
+/// distance is a bound, not exact.
+#[derive(Debug, Clone)]
+pub struct TriangularPrism {
+    radius: f64,
+    height: f64,
+}
+
+impl TriangularPrism {
+    pub fn new(radius: f64, height: f64) -> Self {
+        TriangularPrism { radius, height }
+    }
+}
+
+impl SignedDistanceFunction for TriangularPrism {
+    fn bbox(&self) -> Aabb {
+        let w = self.radius * 3.0_f64.sqrt() / 2.0;
+        Aabb::new(Vec3::new(-w, -self.height / 2.0, -self.radius / 2.0)).expanded(Vec3::new(
+            w,
+            self.height / 2.0,
+            self.radius,
+        ))
+    }
+
+    fn dist(&self, p: &Vec3) -> f64 {
+        let sides = (p.x.abs() * 0.866_025_403_784_438_6 + p.z * 0.5).max(-p.z) - self.radius * 0.5;
+        sides.max(p.y.abs() - self.height / 2.0)
+    }
+}
+
+/// Ellipsoid centered at the origin with the given radii along each axis. The
+/// distance is only an approximation that is exact on the surface.
+#[derive(Debug, Clone)]
+pub struct Ellipsoid {
+    radii: Vec3,
+}
+
+impl Ellipsoid {
+    pub fn new(radii: Vec3) -> Self {
+        Ellipsoid { radii }
+    }
+}
+
+impl SignedDistanceFunction for Ellipsoid {
+    fn bbox(&self) -> Aabb {
+        Aabb::new(-self.radii).expanded(self.radii)
+    }
+
+    fn dist(&self, p: &Vec3) -> f64 {
+        let k0 = (*p / self.radii).norm();
+        let k1 = (*p / (self.radii * self.radii)).norm();
+
+        if k1 == 0.0 {
+            return -self.radii.x.min(self.radii.y).min(self.radii.z);
+        }
+
+        k0 * (k0 - 1.0) / k1
+    }
+}
+
+/// Regular octahedron centered at the origin whose vertices are at distance
+/// `size` along the axes.
+#[derive(Debug, Clone)]
+pub struct Octahedron {
+    size: f64,
+}
+
+impl Octahedron {
+    pub fn new(size: f64) -> Self {
+        Octahedron { size }
+    }
+}
+
+impl SignedDistanceFunction for Octahedron {
+    fn bbox(&self) -> Aabb {
+        Aabb::cube(Vec3::zero(), self.size * 2.0)
+    }
+
+    fn dist(&self, p: &Vec3) -> f64 {
+        let s = self.size;
+        let p = Vec3::new(p.x.abs(), p.y.abs(), p.z.abs());
+        let m = p.x + p.y + p.z - s;
+
+        let q = if 3.0 * p.x < m {
+            p
+        } else if 3.0 * p.y < m {
+            Vec3::new(p.y, p.z, p.x)
+        } else if 3.0 * p.z < m {
+            Vec3::new(p.z, p.x, p.y)
+        } else {
+            return m * 0.577_350_269_189_625_8;
+        };
+
+        let k = (0.5 * (q.z - q.y + s)).clamp(0.0, s);
+        Vec3::new(q.x, q.y - s + k, q.z - k).norm()
+    }
+}
+
+/// The half-space of the points `p` such that `p.dot(normal) <= offset` clipped
+/// to `bounds` so that it has a finite bounding box. It's mostly useful to cut
+/// other shapes with `intersection` and `difference`.
+#[derive(Debug, Clone)]
+pub struct HalfSpace {
+    normal: Vec3,
+    offset: f64,
+    bounds: Aabb,
+}
+
+impl HalfSpace {
+    pub fn new(normal: Vec3, offset: f64, bounds: Aabb) -> Self {
+        HalfSpace {
+            normal: normal.normalized(),
+            offset,
+            bounds,
+        }
+    }
+}
+
+impl SignedDistanceFunction for HalfSpace {
+    fn bbox(&self) -> Aabb {
+        self.bounds.clone()
+    }
+
+    fn dist(&self, p: &Vec3) -> f64 {
+        let bounds = Cube::new(self.bounds.dimensions()).dist(&(*p - self.bounds.center()));
+        (p.dot(self.normal) - self.offset).max(bounds)
+    }
+
+    fn gradient(&self, p: &Vec3) -> Option<Vec3> {
+        let bounds = Cube::new(self.bounds.dimensions());
+        let q = *p - self.bounds.center();
+
+        if p.dot(self.normal) - self.offset >= bounds.dist(&q) {
+            Some(self.normal)
+        } else {
+            bounds.gradient(&q)
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Transformed<S> {
+    sdf: S,
+    matrix: Mat4,
+    inverse_matrix: Mat4,
+}
+
+impl<S: SignedDistanceFunction> SignedDistanceFunction for Transformed<S> {
+    fn bbox(&self) -> Aabb {
+        self.sdf.bbox().transform(&self.matrix)
+    }
+
+    fn dist(&self, p: &Vec3) -> f64 {
+        let q = p.transform(&self.inverse_matrix);
+        self.sdf.dist(&q)
+    }
+
+    fn gradient(&self, p: &Vec3) -> Option<Vec3> {
+        let g = self.sdf.gradient(&p.transform(&self.inverse_matrix))?;
+        Some(self.inverse_matrix.transpose().transform_normal(&g))
+    }
+
+    fn orbit_trap(&self, p: &Vec3) -> Option<f64> {
+        self.sdf.orbit_trap(&p.transform(&self.inverse_matrix))
+    }
+
+    fn material_at(&self, p: &Vec3) -> Option<&Material> {
+        self.sdf.material_at(&p.transform(&self.inverse_matrix))
+    }
+}
+
+#[derive(Debug)]
+pub struct Union<S1, S2> {
+    left: S1,
+    right: S2,
+}
+
+impl<S1, S2> SignedDistanceFunction for Union<S1, S2>
+where
+    S1: SignedDistanceFunction,
+    S2: SignedDistanceFunction,
+{
+    fn bbox(&self) -> Aabb {
+        self.left.bbox().union(&self.right.bbox())
+    }
+
+    fn dist(&self, p: &Vec3) -> f64 {
+        let ld = self.left.dist(p);
+        let rd = self.right.dist(p);
+
+        ld.min(rd)
+    }
+
+    fn gradient(&self, p: &Vec3) -> Option<Vec3> {
+        if self.left.dist(p) <= self.right.dist(p) {
+            self.left.gradient(p)
+        } else {
+            self.right.gradient(p)
+        }
+    }
+
+    fn orbit_trap(&self, p: &Vec3) -> Option<f64> {
+        if self.left.dist(p) <= self.right.dist(p) {
+            self.left.orbit_trap(p)
+        } else {
+            self.right.orbit_trap(p)
+        }
+    }
+
+    fn material_at(&self, p: &Vec3) -> Option<&Material> {
+        if self.left.dist(p) <= self.right.dist(p) {
+            self.left.material_at(p)
+        } else {
+            self.right.material_at(p)
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Intersection<S1, S2> {
+    left: S1,
+    right: S2,
+}
+
+impl<S1, S2> SignedDistanceFunction for Intersection<S1, S2>
+where
+    S1: SignedDistanceFunction,
+    S2: SignedDistanceFunction,
+{
+    fn bbox(&self) -> Aabb {
+        let bbox = self.left.bbox().intersection(&self.right.bbox());
+
+        match bbox {
+            Some(b) => b,
+            None => {
+                println!(
+                    "no intersection between shapes {:?} {:?}",
+                    self.left, self.right
+                );
+                Aabb::new(Vec3::zero())
+            }
+        }
+    }
+
+    fn dist(&self, p: &Vec3) -> f64 {
+        let ld = self.left.dist(p);
+        let rd = self.right.dist(p);
+
+        ld.max(rd)
+    }
+
+    fn gradient(&self, p: &Vec3) -> Option<Vec3> {
+        if self.left.dist(p) >= self.right.dist(p) {
+            self.left.gradient(p)
+        } else {
+            self.right.gradient(p)
+        }
+    }
+
+    fn orbit_trap(&self, p: &Vec3) -> Option<f64> {
+        if self.left.dist(p) >= self.right.dist(p) {
+            self.left.orbit_trap(p)
+        } else {
+            self.right.orbit_trap(p)
+        }
+    }
+
+    fn material_at(&self, p: &Vec3) -> Option<&Material> {
+        if self.left.dist(p) >= self.right.dist(p) {
+            self.left.material_at(p)
+        } else {
+            self.right.material_at(p)
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Difference<S1, S2> {
+    left: S1,
+    right: S2,
+}
+
+impl<S1, S2> SignedDistanceFunction for Difference<S1, S2>
+where
+    S1: SignedDistanceFunction,
+    S2: SignedDistanceFunction,
+{
+    fn bbox(&self) -> Aabb {
+        self.left.bbox()
+    }
+
+    fn dist(&self, p: &Vec3) -> f64 {
+        let ld = self.left.dist(p);
+        let rd = self.right.dist(p);
+
+        ld.max(-rd)
+    }
+
+    fn gradient(&self, p: &Vec3) -> Option<Vec3> {
+        if self.left.dist(p) >= -self.right.dist(p) {
+            self.left.gradient(p)
+        } else {
+            Some(-self.right.gradient(p)?)
+        }
+    }
+
+    fn orbit_trap(&self, p: &Vec3) -> Option<f64> {
+        if self.left.dist(p) >= -self.right.dist(p) {
+            self.left.orbit_trap(p)
+        } else {
+            self.right.orbit_trap(p)
+        }
+    }
+
+    fn material_at(&self, p: &Vec3) -> Option<&Material> {
+        if self.left.dist(p) >= -self.right.dist(p) {
+            self.left.material_at(p)
+        } else {
+            self.right.material_at(p)
+        }
+    }
+}
+
+/// How the surfaces of the smooth CSG operations are blended together. The
+/// parameter is the size of the blending region, that is roughly the radius of
+/// the fillet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Blend {
+    /// Polynomial smooth minimum, the surfaces are blended only where they're
+    /// closer than the given radius to each other.
+    Polynomial(f64),
+
+    /// Exponential smooth minimum, the blending is smoother but it affects the
+    /// whole surface, albeit negligibly far away from the other surface.
+    Exponential(f64),
+}
+
+impl Blend {
+    /// Smooth version of `a.min(b)`.
+    pub fn smooth_min(self, a: f64, b: f64) -> f64 {
+        match self {
+            Blend::Polynomial(k) => {
+                if k <= 0.0 {
+                    return a.min(b);
+                }
+
+                let h = (k - (a - b).abs()).max(0.0) / k;
+                a.min(b) - h * h * k / 4.0
+            }
+            Blend::Exponential(k) => {
+                if k <= 0.0 {
+                    return a.min(b);
+                }
+
+                // -k ln(e^(-a/k) + e^(-b/k)) rewritten to avoid overflows
+                a.min(b) - k * (-(a - b).abs() / k).exp().ln_1p()
+            }
+        }
+    }
+
+    /// Smooth version of `a.max(b)`.
+    pub fn smooth_max(self, a: f64, b: f64) -> f64 {
+        -self.smooth_min(-a, -b)
+    }
+
+    /// The maximum difference between `smooth_min` and `min`, that is how much
+    /// a smooth union can grow over the plain one.
+    fn max_offset(self) -> f64 {
+        match self {
+            Blend::Polynomial(k) => k.max(0.0) / 4.0,
+            Blend::Exponential(k) => k.max(0.0) * std::f64::consts::LN_2,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SmoothUnion<S1, S2> {
+    left: S1,
+    right: S2,
+    blend: Blend,
+}
+
+impl<S1, S2> SignedDistanceFunction for SmoothUnion<S1, S2>
+where
+    S1: SignedDistanceFunction,
+    S2: SignedDistanceFunction,
+{
+    fn bbox(&self) -> Aabb {
+        let bbox = self.left.bbox().union(&self.right.bbox());
+        expanded_by(bbox, Vec3::replicate(self.blend.max_offset()))
+    }
+
+    fn dist(&self, p: &Vec3) -> f64 {
+        self.blend.smooth_min(self.left.dist(p), self.right.dist(p))
+    }
+
+    fn orbit_trap(&self, p: &Vec3) -> Option<f64> {
+        if self.left.dist(p) <= self.right.dist(p) {
+            self.left.orbit_trap(p)
+        } else {
+            self.right.orbit_trap(p)
+        }
+    }
+
+    fn material_at(&self, p: &Vec3) -> Option<&Material> {
+        if self.left.dist(p) <= self.right.dist(p) {
+            self.left.material_at(p)
+        } else {
+            self.right.material_at(p)
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SmoothIntersection<S1, S2> {
+    left: S1,
+    right: S2,
+    blend: Blend,
+}
+
+impl<S1, S2> SignedDistanceFunction for SmoothIntersection<S1, S2>
+where
+    S1: SignedDistanceFunction,
+    S2: SignedDistanceFunction,
+{
+    fn bbox(&self) -> Aabb {
+        // the smooth intersection is always contained in the plain one
+        self.left
+            .bbox()
+            .intersection(&self.right.bbox())
+            .unwrap_or_else(|| Aabb::new(Vec3::zero()))
+    }
+
+    fn dist(&self, p: &Vec3) -> f64 {
+        self.blend.smooth_max(self.left.dist(p), self.right.dist(p))
+    }
+
+    fn orbit_trap(&self, p: &Vec3) -> Option<f64> {
+        if self.left.dist(p) >= self.right.dist(p) {
+            self.left.orbit_trap(p)
+        } else {
+            self.right.orbit_trap(p)
+        }
+    }
+
+    fn material_at(&self, p: &Vec3) -> Option<&Material> {
+        if self.left.dist(p) >= self.right.dist(p) {
+            self.left.material_at(p)
+        } else {
+            self.right.material_at(p)
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SmoothDifference<S1, S2> {
+    left: S1,
+    right: S2,
+    blend: Blend,
+}
+
+impl<S1, S2> SignedDistanceFunction for SmoothDifference<S1, S2>
+where
+    S1: SignedDistanceFunction,
+    S2: SignedDistanceFunction,
+{
+    fn bbox(&self) -> Aabb {
+        self.left.bbox()
+    }
+
+    fn dist(&self, p: &Vec3) -> f64 {
+        self.blend
+            .smooth_max(self.left.dist(p), -self.right.dist(p))
+    }
+
+    fn orbit_trap(&self, p: &Vec3) -> Option<f64> {
+        if self.left.dist(p) >= -self.right.dist(p) {
+            self.left.orbit_trap(p)
+        } else {
+            self.right.orbit_trap(p)
+        }
+    }
+
+    fn material_at(&self, p: &Vec3) -> Option<&Material> {
+        if self.left.dist(p) >= -self.right.dist(p) {
+            self.left.material_at(p)
+        } else {
+            self.right.material_at(p)
+        }
+    }
+}
+
+pub struct Displaced<S, F> {
+    sdf: S,
+    displacement: F,
+    amplitude: f64,
+    lipschitz: f64,
+}
+
+impl<S: std::fmt::Debug, F> std::fmt::Debug for Displaced<S, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Displaced")
+            .field("sdf", &self.sdf)
+            .field("amplitude", &self.amplitude)
+            .field("lipschitz", &self.lipschitz)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S, F> SignedDistanceFunction for Displaced<S, F>
+where
+    S: SignedDistanceFunction,
+    F: Fn(&Vec3) -> f64,
+{
+    fn bbox(&self) -> Aabb {
+        expanded_by(self.sdf.bbox(), Vec3::replicate(self.amplitude))
+    }
+
+    fn dist(&self, p: &Vec3) -> f64 {
+        // the displaced distance changes at most by 1 + lipschitz per unit,
+        // hence scaling it keeps it a lower bound of the real distance
+        (self.sdf.dist(p) - (self.displacement)(p)) / (1.0 + self.lipschitz)
+    }
+
+    fn orbit_trap(&self, p: &Vec3) -> Option<f64> {
+        self.sdf.orbit_trap(p)
+    }
+
+    fn material_at(&self, p: &Vec3) -> Option<&Material> {
+        self.sdf.material_at(p)
+    }
+}
+
+#[derive(Debug)]
+pub struct Repeated<S> {
+    sdf: S,
+    spacing: Vec3,
+    counts: [u32; 3],
+}
+
+impl<S: SignedDistanceFunction> SignedDistanceFunction for Repeated<S> {
+    fn bbox(&self) -> Aabb {
+        let bbox = self.sdf.bbox();
+        let [nx, ny, nz] = self.counts.map(|n| f64::from(n.max(1) - 1));
+        let last = self.spacing * Vec3::new(nx, ny, nz);
+
+        bbox.union(&Aabb::new(bbox.min() + last).expanded(bbox.max() + last))
+    }
+
+    fn dist(&self, p: &Vec3) -> f64 {
+        self.sdf.dist(&self.local(p))
+    }
+
+    fn orbit_trap(&self, p: &Vec3) -> Option<f64> {
+        self.sdf.orbit_trap(&self.local(p))
+    }
+
+    fn material_at(&self, p: &Vec3) -> Option<&Material> {
+        self.sdf.material_at(&self.local(p))
+    }
+}
+
+impl<S> Repeated<S> {
+    /// Map `p` to the closest copy of the shape.
+    fn local(&self, p: &Vec3) -> Vec3 {
+        let [nx, ny, nz] = self.counts.map(|n| f64::from(n.max(1) - 1));
+
+        Vec3::new(
+            repeat(p.x, self.spacing.x, nx),
+            repeat(p.y, self.spacing.y, ny),
+            repeat(p.z, self.spacing.z, nz),
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct RepeatedWithin<S> {
+    sdf: S,
+    spacing: Vec3,
+    bounds: Aabb,
+}
+
+impl<S: SignedDistanceFunction> SignedDistanceFunction for RepeatedWithin<S> {
+    fn bbox(&self) -> Aabb {
+        self.bounds.clone()
+    }
+
+    fn dist(&self, p: &Vec3) -> f64 {
+        let bounds = Cube::new(self.bounds.dimensions()).dist(&(*p - self.bounds.center()));
+        self.sdf.dist(&self.local(p)).max(bounds)
+    }
+
+    fn orbit_trap(&self, p: &Vec3) -> Option<f64> {
+        self.sdf.orbit_trap(&self.local(p))
+    }
+
+    fn material_at(&self, p: &Vec3) -> Option<&Material> {
+        self.sdf.material_at(&self.local(p))
+    }
+}
+
+impl<S> RepeatedWithin<S> {
+    /// Map `p` to the closest copy of the shape.
+    fn local(&self, p: &Vec3) -> Vec3 {
+        Vec3::new(
+            repeat_infinitely(p.x, self.spacing.x),
+            repeat_infinitely(p.y, self.spacing.y),
+            repeat_infinitely(p.z, self.spacing.z),
+        )
+    }
+}
+
+/// Fold `v` into the copy at `spacing * i` closest to it with `i` between 0
+/// and `last`.
+fn repeat(v: f64, spacing: f64, last: f64) -> f64 {
+    if spacing == 0.0 {
+        return v;
+    }
+
+    v - spacing * (v / spacing).round().clamp(0.0, last)
+}
+
+fn repeat_infinitely(v: f64, spacing: f64) -> f64 {
+    if spacing == 0.0 {
+        return v;
+    }
+
+    v - spacing * (v / spacing).round()
+}
+
+#[derive(Debug)]
+pub struct Mirrored<S> {
+    sdf: S,
+    axis: Axis,
+}
+
+impl<S: SignedDistanceFunction> SignedDistanceFunction for Mirrored<S> {
+    fn bbox(&self) -> Aabb {
+        let bbox = self.sdf.bbox();
+        let (mut min, mut max) = (bbox.min(), bbox.max());
+
+        // only the positive side of the shape is kept and then mirrored
+        match self.axis {
+            Axis::X => {
+                max.x = max.x.max(0.0);
+                min.x = -max.x;
+            }
+            Axis::Y => {
+                max.y = max.y.max(0.0);
+                min.y = -max.y;
+            }
+            Axis::Z => {
+                max.z = max.z.max(0.0);
+                min.z = -max.z;
+            }
+        }
+
+        Aabb::new(min).expanded(max)
+    }
+
+    fn dist(&self, p: &Vec3) -> f64 {
+        self.sdf.dist(&self.local(p))
+    }
+
+    fn orbit_trap(&self, p: &Vec3) -> Option<f64> {
+        self.sdf.orbit_trap(&self.local(p))
+    }
+
+    fn material_at(&self, p: &Vec3) -> Option<&Material> {
+        self.sdf.material_at(&self.local(p))
+    }
+}
+
+impl<S> Mirrored<S> {
+    /// Map `p` to the positive side of the axis.
+    fn local(&self, p: &Vec3) -> Vec3 {
+        let mut q = *p;
+        match self.axis {
+            Axis::X => q.x = q.x.abs(),
+            Axis::Y => q.y = q.y.abs(),
+            Axis::Z => q.z = q.z.abs(),
+        }
+
+        q
+    }
+}
+
+#[derive(Debug)]
+pub struct Twisted<S> {
+    sdf: S,
+    rate: f64,
+
+    // how much the twist stretches the distances at most
+    stretch: f64,
+}
+
+impl<S: SignedDistanceFunction> SignedDistanceFunction for Twisted<S> {
+    fn bbox(&self) -> Aabb {
+        // the points rotate around the Y axis, hence they stay inside the
+        // cylinder that contains the bbox
+        let bbox = self.sdf.bbox();
+        let r = max_radius(&bbox, Axis::Y);
+
+        Aabb::new(Vec3::new(-r, bbox.min().y, -r)).expanded(Vec3::new(r, bbox.max().y, r))
+    }
+
+    fn dist(&self, p: &Vec3) -> f64 {
+        self.sdf.dist(&self.local(p)) / self.stretch
+    }
+
+    fn orbit_trap(&self, p: &Vec3) -> Option<f64> {
+        self.sdf.orbit_trap(&self.local(p))
+    }
+
+    fn material_at(&self, p: &Vec3) -> Option<&Material> {
+        self.sdf.material_at(&self.local(p))
+    }
+}
+
+impl<S> Twisted<S> {
+    /// Undo the twist of `p`.
+    fn local(&self, p: &Vec3) -> Vec3 {
+        let (s, c) = (self.rate * p.y).sin_cos();
+        Vec3::new(c * p.x - s * p.z, p.y, s * p.x + c * p.z)
+    }
+}
+
+#[derive(Debug)]
+pub struct Bent<S> {
+    sdf: S,
+    rate: f64,
+
+    // how much the bend stretches the distances at most
+    stretch: f64,
+}
+
+impl<S: SignedDistanceFunction> SignedDistanceFunction for Bent<S> {
+    fn bbox(&self) -> Aabb {
+        // the points rotate around the Z axis, hence they stay inside the
+        // cylinder that contains the bbox
+        let bbox = self.sdf.bbox();
+        let r = max_radius(&bbox, Axis::Z);
+
+        Aabb::new(Vec3::new(-r, -r, bbox.min().z)).expanded(Vec3::new(r, r, bbox.max().z))
+    }
+
+    fn dist(&self, p: &Vec3) -> f64 {
+        self.sdf.dist(&self.local(p)) / self.stretch
+    }
+
+    fn orbit_trap(&self, p: &Vec3) -> Option<f64> {
+        self.sdf.orbit_trap(&self.local(p))
+    }
+
+    fn material_at(&self, p: &Vec3) -> Option<&Material> {
+        self.sdf.material_at(&self.local(p))
+    }
+}
+
+impl<S> Bent<S> {
+    /// Undo the bend of `p`.
+    fn local(&self, p: &Vec3) -> Vec3 {
+        let (s, c) = (self.rate * p.x).sin_cos();
+        Vec3::new(c * p.x - s * p.y, s * p.x + c * p.y, p.z)
+    }
+}
+
+/// The maximum distance between the given axis and the points of the bbox.
+fn max_radius(bbox: &Aabb, axis: Axis) -> f64 {
+    let (min, max) = (bbox.min(), bbox.max());
+    let far = Vec3::new(
+        min.x.abs().max(max.x.abs()),
+        min.y.abs().max(max.y.abs()),
+        min.z.abs().max(max.z.abs()),
+    );
+
+    match axis {
+        Axis::X => far.y.hypot(far.z),
+        Axis::Y => far.x.hypot(far.z),
+        Axis::Z => far.x.hypot(far.y),
+    }
+}
+
+#[derive(Debug)]
+pub struct Elongated<S> {
+    sdf: S,
+    amount: Vec3,
+}
+
+impl<S: SignedDistanceFunction> SignedDistanceFunction for Elongated<S> {
+    fn bbox(&self) -> Aabb {
+        expanded_by(self.sdf.bbox(), self.amount)
+    }
+
+    fn dist(&self, p: &Vec3) -> f64 {
+        self.sdf.dist(&self.local(p))
+    }
+
+    fn orbit_trap(&self, p: &Vec3) -> Option<f64> {
+        self.sdf.orbit_trap(&self.local(p))
+    }
+
+    fn material_at(&self, p: &Vec3) -> Option<&Material> {
+        self.sdf.material_at(&self.local(p))
+    }
+}
+
+impl<S> Elongated<S> {
+    /// Move `p` towards the origin by at most the elongation.
+    fn local(&self, p: &Vec3) -> Vec3 {
+        let h = self.amount;
+        Vec3::new(
+            p.x - p.x.clamp(-h.x, h.x),
+            p.y - p.y.clamp(-h.y, h.y),
+            p.z - p.z.clamp(-h.z, h.z),
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct Rounded<S> {
+    sdf: S,
+    radius: f64,
+}
+
+impl<S: SignedDistanceFunction> SignedDistanceFunction for Rounded<S> {
+    fn bbox(&self) -> Aabb {
+        expanded_by(self.sdf.bbox(), Vec3::replicate(self.radius))
+    }
+
+    fn dist(&self, p: &Vec3) -> f64 {
+        self.sdf.dist(p) - self.radius
+    }
+
+    fn gradient(&self, p: &Vec3) -> Option<Vec3> {
+        self.sdf.gradient(p)
+    }
+
+    fn orbit_trap(&self, p: &Vec3) -> Option<f64> {
+        self.sdf.orbit_trap(p)
+    }
+
+    fn material_at(&self, p: &Vec3) -> Option<&Material> {
+        self.sdf.material_at(p)
+    }
+}
+
+#[derive(Debug)]
+pub struct Onion<S> {
+    sdf: S,
+    thickness: f64,
+}
+
+impl<S: SignedDistanceFunction> SignedDistanceFunction for Onion<S> {
+    fn bbox(&self) -> Aabb {
+        expanded_by(self.sdf.bbox(), Vec3::replicate(self.thickness))
+    }
+
+    fn dist(&self, p: &Vec3) -> f64 {
+        self.sdf.dist(p).abs() - self.thickness
+    }
+
+    fn gradient(&self, p: &Vec3) -> Option<Vec3> {
+        Some(self.sdf.gradient(p)? * self.sdf.dist(p).signum())
+    }
+
+    fn orbit_trap(&self, p: &Vec3) -> Option<f64> {
+        self.sdf.orbit_trap(p)
+    }
+
+    fn material_at(&self, p: &Vec3) -> Option<&Material> {
+        self.sdf.material_at(p)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WithMaterial<S> {
+    sdf: S,
+    material: Material,
+}
+
+impl<S: SignedDistanceFunction> SignedDistanceFunction for WithMaterial<S> {
+    fn bbox(&self) -> Aabb {
+        self.sdf.bbox()
+    }
+
+    fn dist(&self, p: &Vec3) -> f64 {
+        self.sdf.dist(p)
+    }
+
+    fn gradient(&self, p: &Vec3) -> Option<Vec3> {
+        self.sdf.gradient(p)
+    }
+
+    fn orbit_trap(&self, p: &Vec3) -> Option<f64> {
+        self.sdf.orbit_trap(p)
+    }
+
+    fn material_at(&self, p: &Vec3) -> Option<&Material> {
+        self.sdf.material_at(p).or(Some(&self.material))
+    }
+}
+
+/// Grow the bbox by `d` on all sides.
+fn expanded_by(bbox: Aabb, d: Vec3) -> Aabb {
+    Aabb::new(bbox.min() - d).expanded(bbox.max() + d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the distance function, bounding box and a point on the surface
+    type Primitive = (Box<dyn Fn(&Vec3) -> f64>, Aabb, Vec3);
+
+    #[test]
+    fn test_sphere_tracing() {
+        let geom = SdfGeometry::new(Sphere::new(1.0)).with_stats();
+
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -2.0));
+        let hit = geom.intersection(&ray).unwrap();
+        assert!((hit.t - 2.0).abs() < 1e-4);
+        let (p, n) = hit.point_and_normal.unwrap();
+        assert!(n.dist(Vec3::new(0.0, 0.0, 1.0)) < 1e-9);
+
+        // secondary rays leaving the surface don't hit it again, but the ones
+        // going inside hit the other side
+        assert!(geom
+            .intersection(&Ray::new(p, Vec3::new(1.0, 0.0, 1.0)))
+            .is_none());
+        let hit = geom
+            .intersection(&Ray::new(p, Vec3::new(0.0, 0.0, -1.0)))
+            .unwrap();
+        assert!((hit.t - 2.0).abs() < 1e-4);
+
+        assert!(geom
+            .intersection(&Ray::new(
+                Vec3::new(0.8, 0.8, 5.0),
+                Vec3::new(0.0, 0.0, -1.0)
+            ))
+            .is_none());
+
+        let stats = geom.stats().unwrap();
+        assert_eq!((stats.rays, stats.hits, stats.exhausted), (4, 2, 0));
+        assert!(stats.average_steps() > 1.0);
+
+        // the normals of shapes without analytic gradients must be precise
+        // even far away from the origin
+        let far = Vec3::new(1e4, 0.0, 0.0);
+        let geom = SdfGeometry::new(
+            Sphere::new(1.0)
+                .smooth_union(Sphere::new(0.5), Blend::Polynomial(0.1))
+                .transformed(Mat4::translate(far)),
+        );
+        let ray = Ray::new(far + Vec3::new(3.0, 0.6, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+        let (p, n) = geom.intersection(&ray).unwrap().point_and_normal.unwrap();
+        assert!(n.dist((p - far).normalized()) < 1e-4);
+    }
+
+    #[test]
+    fn test_gradients() {
+        let shapes: Vec<BoxedSdf> = vec![
+            Box::new(Sphere::new(1.0)),
+            Box::new(Cube::new(Vec3::new(1.0, 2.0, 3.0))),
+            Box::new(Cylinder::new(0.5, 2.0)),
+            Box::new(Torus::new(1.0, 0.25)),
+            Box::new(Capsule::new(Vec3::zero(), Vec3::new(0.0, 0.0, 1.0), 0.5)),
+            Box::new(HalfSpace::new(
+                Vec3::new(1.0, 1.0, 0.0),
+                0.2,
+                Aabb::cube(Vec3::zero(), 2.0),
+            )),
+            Box::new(
+                Cube::new(Vec3::replicate(1.0))
+                    .difference(Sphere::new(0.6))
+                    .union(Torus::new(1.2, 0.2))
+                    .transformed(Mat4::rotate(Vec3::new(1.0, 1.0, 0.0), 0.5)),
+            ),
+            Box::new(Cylinder::new(0.5, 1.0).rounded(0.1).onion(0.05)),
+        ];
+
+        for sdf in shapes {
+            for i in 0..50 {
+                let f = f64::from(i);
+                let p = Vec3::new(
+                    (f * 0.7).sin() + 0.1,
+                    (f * 1.3).cos(),
+                    (f * 0.3).sin() * 1.5 + 0.1,
+                ) * 1.3;
+
+                let e = 1e-6;
+                let numeric = Vec3::new(
+                    sdf.dist(&(p + Vec3::new(e, 0.0, 0.0)))
+                        - sdf.dist(&(p - Vec3::new(e, 0.0, 0.0))),
+                    sdf.dist(&(p + Vec3::new(0.0, e, 0.0)))
+                        - sdf.dist(&(p - Vec3::new(0.0, e, 0.0))),
+                    sdf.dist(&(p + Vec3::new(0.0, 0.0, e)))
+                        - sdf.dist(&(p - Vec3::new(0.0, 0.0, e))),
+                )
+                .normalized();
+
+                let g = sdf.gradient(&p).unwrap().normalized();
+                assert!(
+                    g.dist(numeric) < 1e-3,
+                    "{:?} at {:?}: {:?} != {:?}",
+                    sdf,
+                    p,
+                    g,
+                    numeric
+                );
+            }
+        }
+
+        // shapes that blend the distances fall back to numeric normals
+        assert!(Sphere::new(1.0)
+            .smooth_union(Sphere::new(0.5), Blend::Polynomial(0.1))
+            .gradient(&Vec3::zero())
+            .is_none());
+    }
+
+    #[test]
+    fn test_primitives() {
+        let primitives: Vec<Primitive> = vec![
+            boxed(Torus::new(1.0, 0.25), Vec3::new(1.25, 0.0, 0.0)),
+            boxed(
+                Capsule::new(Vec3::zero(), Vec3::new(0.0, 0.0, 1.0), 0.5),
+                Vec3::new(0.0, 0.0, 1.5),
+            ),
+            boxed(Cone::new(2.0, 1.0, 0.5), Vec3::new(0.75, 0.0, 0.0)),
+            boxed(
+                RoundedBox::new(Vec3::new(1.0, 2.0, 3.0), 0.1),
+                Vec3::new(0.5, 0.0, 0.0),
+            ),
+            boxed(HexagonalPrism::new(1.0, 2.0), Vec3::new(0.0, 0.0, 1.0)),
+            boxed(TriangularPrism::new(1.0, 2.0), Vec3::new(0.0, 0.0, 1.0)),
+            boxed(
+                Ellipsoid::new(Vec3::new(1.0, 2.0, 3.0)),
+                Vec3::new(0.0, 2.0, 0.0),
+            ),
+            boxed(Octahedron::new(1.0), Vec3::new(0.0, 1.0, 0.0)),
+            boxed(
+                HalfSpace::new(Vec3::new(0.0, 1.0, 0.0), 0.5, Aabb::cube(Vec3::zero(), 2.0)),
+                Vec3::new(0.0, 0.5, 0.0),
+            ),
+        ];
+
+        for (dist, bbox, surface_point) in primitives {
+            assert!(dist(&surface_point).abs() < 1e-9);
+            assert!(dist(&(surface_point * 0.99)) < 0.0);
+
+            // all the points outside the bounding box must be outside the shape
+            let (min, max) = (bbox.min(), bbox.max());
+            for i in 0..=10 {
+                for j in 0..=10 {
+                    let (u, v) = (f64::from(i) / 10.0, f64::from(j) / 10.0);
+                    let lerp = |a: f64, b: f64, t: f64| a + (b - a) * t;
+
+                    for p in [
+                        Vec3::new(min.x - 1e-6, lerp(min.y, max.y, u), lerp(min.z, max.z, v)),
+                        Vec3::new(max.x + 1e-6, lerp(min.y, max.y, u), lerp(min.z, max.z, v)),
+                        Vec3::new(lerp(min.x, max.x, u), min.y - 1e-6, lerp(min.z, max.z, v)),
+                        Vec3::new(lerp(min.x, max.x, u), max.y + 1e-6, lerp(min.z, max.z, v)),
+                        Vec3::new(lerp(min.x, max.x, u), lerp(min.y, max.y, v), min.z - 1e-6),
+                        Vec3::new(lerp(min.x, max.x, u), lerp(min.y, max.y, v), max.z + 1e-6),
+                    ] {
+                        assert!(dist(&p) > 0.0, "{:?} inside", p);
+                    }
+                }
+            }
+        }
+
+        assert!(
+            (Octahedron::new(1.0).dist(&Vec3::replicate(1.0)) - 2.0 / 3.0_f64.sqrt()).abs() < 1e-9
+        );
+        assert!((Cone::new(2.0, 1.0, 0.0).dist(&Vec3::new(0.0, 2.0, 0.0)) - 1.0).abs() < 1e-9);
+    }
+
+    fn boxed<S: SignedDistanceFunction + 'static>(sdf: S, surface_point: Vec3) -> Primitive {
+        let bbox = sdf.bbox();
+        (Box::new(move |p| sdf.dist(p)), bbox, surface_point)
+    }
+
+    #[test]
+    fn test_blend() {
+        for blend in [Blend::Polynomial(0.5), Blend::Exponential(0.1)] {
+            assert!((blend.smooth_min(1.0, 1.0) - (1.0 - blend.max_offset())).abs() < 1e-12);
+            assert!((blend.smooth_max(1.0, 1.0) - (1.0 + blend.max_offset())).abs() < 1e-12);
+
+            assert!((blend.smooth_min(1.0, 5.0) - 1.0).abs() < 1e-9);
+            assert!((blend.smooth_max(1.0, 5.0) - 5.0).abs() < 1e-9);
+            assert!(blend.smooth_min(1.0, 1.2) < 1.0);
+        }
+
+        // the spheres touch at the origin, the union grows there
+        let spheres = || {
+            (
+                Sphere::new(1.0).transformed(Mat4::translate(Vec3::new(-1.0, 0.0, 0.0))),
+                Sphere::new(1.0).transformed(Mat4::translate(Vec3::new(1.0, 0.0, 0.0))),
+            )
+        };
+
+        let (a, b) = spheres();
+        let union = a.smooth_union(b, Blend::Polynomial(0.4));
+        assert!((union.dist(&Vec3::new(0.0, 0.0, 0.0)) + 0.1).abs() < 1e-9);
+        assert!(union.dist(&Vec3::new(0.0, 0.3, 0.0)) < 0.0);
+        assert_eq!(
+            union.bbox(),
+            Aabb::new(Vec3::new(-2.1, -1.1, -1.1)).expanded(Vec3::new(2.1, 1.1, 1.1))
+        );
+
+        let (a, b) = spheres();
+        let difference = a.smooth_difference(b, Blend::Polynomial(0.4));
+        assert!(difference.dist(&Vec3::new(-0.05, 0.0, 0.0)) > 0.0);
+        assert!(difference.dist(&Vec3::new(-1.0, 0.0, 0.0)) < 0.0);
+    }
+
+    #[test]
+    fn test_domain_operators() {
+        let spheres = Sphere::new(0.5).repeated(Vec3::new(2.0, 0.0, 3.0), [3, 5, 2]);
+        assert_eq!(
+            spheres.bbox(),
+            Aabb::new(Vec3::replicate(-0.5)).expanded(Vec3::new(4.5, 0.5, 3.5))
+        );
+        assert_eq!(spheres.dist(&Vec3::new(4.0, 0.0, 3.0)), -0.5);
+        assert_eq!(spheres.dist(&Vec3::new(1.0, 0.0, 0.0)), 0.5);
+        assert_eq!(spheres.dist(&Vec3::new(7.0, 0.0, 0.0)), 2.5);
+
+        let spheres =
+            Sphere::new(0.5).repeated_within(Vec3::replicate(2.0), Aabb::cube(Vec3::zero(), 100.0));
+        assert_eq!(spheres.dist(&Vec3::new(-40.0, 20.0, 0.0)), -0.5);
+        assert_eq!(spheres.dist(&Vec3::new(51.0, 0.0, 0.0)), 1.0);
+
+        let mirrored = Sphere::new(1.0)
+            .transformed(Mat4::translate(Vec3::new(2.0, 0.0, 0.0)))
+            .mirrored(Axis::X);
+        assert!(mirrored.dist(&Vec3::new(-2.0, 0.0, 0.0)) < 0.0);
+        assert_eq!(mirrored.bbox().min().x, -3.0);
+
+        let column = Cube::new(Vec3::new(1.0, 4.0, 1.0)).twisted(1.0);
+        assert_eq!(column.dist(&Vec3::zero()), -0.5 / column.stretch);
+        let r = 0.5_f64.hypot(0.5);
+        assert_eq!(
+            column.bbox(),
+            Aabb::new(Vec3::new(-r, -2.0, -r)).expanded(Vec3::new(r, 2.0, r))
+        );
+
+        let pill = Sphere::new(1.0).elongated(Vec3::new(0.0, 2.0, 0.0));
+        assert_eq!(pill.dist(&Vec3::new(0.0, 2.5, 0.0)), -0.5);
+        assert_eq!(pill.dist(&Vec3::new(2.0, 1.0, 0.0)), 1.0);
+
+        let shell = Sphere::new(1.0).onion(0.1);
+        assert!((shell.dist(&Vec3::zero()) - 0.9).abs() < 1e-9);
+        assert!(shell.dist(&Vec3::new(1.05, 0.0, 0.0)) < 0.0);
+        assert!((Sphere::new(1.0).rounded(0.5).dist(&Vec3::zero()) + 1.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_displaced() {
+        let sphere = Sphere::new(1.0).displaced(|_| 0.5, 0.5, 0.0);
+        assert_eq!(sphere.dist(&Vec3::new(2.0, 0.0, 0.0)), 0.5);
+        assert_eq!(sphere.bbox(), Aabb::cube(Vec3::zero(), 3.0));
+
+        let bumpy =
+            SdfGeometry::new(Sphere::new(1.0).displaced(|p| (p.x * 10.0).sin() * 0.1, 0.1, 1.0));
+
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        let t = bumpy.intersection(&ray).unwrap().t;
+        assert!((t - 4.0).abs() < 1e-4);
+
+        // the bumps are at most 0.1 tall
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.1, 0.0, -1.0));
+        let t = bumpy.intersection(&ray).unwrap().t;
+        let d = ray.point_at(t).norm() - 1.0;
+        assert!(d.abs() <= 0.1 + 1e-4);
+        assert!((d - (ray.point_at(t).x * 10.0).sin() * 0.1).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_materials() {
+        let red = Material::lambertian(Vec3::new(1.0, 0.0, 0.0));
+        let blue = Material::lambertian(Vec3::new(0.0, 0.0, 1.0));
+
+        // a cube drilled along Y, next to a sphere without a material
+        let drilled = Cube::new(Vec3::replicate(2.0))
+            .with_material(red.clone())
+            .difference(Cylinder::new(0.3, 3.0).with_material(blue.clone()))
+            .union(Sphere::new(0.5).transformed(Mat4::translate(Vec3::new(3.0, 0.0, 0.0))))
+            .transformed(Mat4::translate(Vec3::new(0.0, 0.0, 1.0)));
+
+        assert_eq!(drilled.material_at(&Vec3::new(0.0, 0.0, 2.0)), Some(&red));
+        assert_eq!(drilled.material_at(&Vec3::new(0.3, 0.5, 1.0)), Some(&blue));
+        assert_eq!(drilled.material_at(&Vec3::new(3.5, 0.0, 1.0)), None);
+
+        let geom = SdfGeometry::new(drilled);
+        let hit = geom
+            .intersection(&Ray::new(
+                Vec3::new(0.0, 0.0, 5.0),
+                Vec3::new(0.0, 0.0, -1.0),
+            ))
+            .unwrap();
+        assert_eq!(hit.material, Some(red.clone()));
+
+        let hit = geom
+            .intersection(&Ray::new(
+                Vec3::new(0.0, 0.5, 1.0),
+                Vec3::new(1.0, 0.0, 0.0),
+            ))
+            .unwrap();
+        assert!((hit.t - 0.3).abs() < 1e-4);
+        assert_eq!(hit.material, Some(blue));
+
+        // the innermost material wins
+        let sphere = Sphere::new(1.0)
+            .with_material(red.clone())
+            .repeated(Vec3::new(3.0, 0.0, 0.0), [2, 1, 1])
+            .with_material(Material::metal(Vec3::zero(), 0.0));
+        assert_eq!(sphere.material_at(&Vec3::new(4.0, 0.0, 0.0)), Some(&red));
+    }
+
+    #[test]
+    fn test_orbit_traps() {
+        let sponge = || MengerSponge::new(2);
+        let far = || Sphere::new(1.0).transformed(Mat4::translate(Vec3::new(10.0, 0.0, 0.0)));
+        let blend = Blend::Polynomial(0.1);
+
+        // all the operators map `p` onto itself, hence the trap of the sponge is
+        // expected everywhere
+        let p = Vec3::new(0.0, 0.0, 0.3);
+        let trap = sponge().orbit_trap(&p);
+        assert!(trap.is_some());
+
+        let operators: Vec<BoxedSdf> = vec![
+            Box::new(sponge().repeated(Vec3::replicate(3.0), [2, 2, 2])),
+            Box::new(
+                sponge().repeated_within(Vec3::replicate(5.0), Aabb::cube(Vec3::zero(), 20.0)),
+            ),
+            Box::new(sponge().mirrored(Axis::X)),
+            Box::new(sponge().twisted(1.0)),
+            Box::new(sponge().bent(1.0)),
+            Box::new(sponge().elongated(Vec3::new(0.0, 1.0, 0.0))),
+            Box::new(sponge().displaced(|_| 0.0, 0.0, 0.0)),
+            Box::new(sponge().smooth_union(far(), blend)),
+            Box::new(far().smooth_union(sponge(), blend)),
+            Box::new(sponge().smooth_intersection(Sphere::new(10.0), blend)),
+            Box::new(sponge().smooth_difference(far(), blend)),
+        ];
+        for op in operators {
+            assert_eq!(op.orbit_trap(&p), trap, "{:?}", op);
+        }
+
+        // the side that doesn't contain `p` is ignored
+        let p = Vec3::new(10.0, 0.0, 0.0);
+        assert_eq!(sponge().smooth_union(far(), blend).orbit_trap(&p), None);
+    }
+}
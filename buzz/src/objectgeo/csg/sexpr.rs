@@ -0,0 +1,423 @@
+//! Textual format to describe CSG shapes as [S-expressions][0] so that they can
+//! be loaded at runtime.
+//!
+//! Each expression is a list whose first element is the name of an operation
+//! followed by its arguments, either numbers or other expressions. Comments
+//! start with `;` and go until the end of the line.
+//!
+//! ```text
+//! ; a rounded cube with a hole
+//! (difference
+//!   (intersection (sphere 0.65) (cube 1 1 1))
+//!   (rotate 1 0 0 90 (cylinder 0.25 1.1)))
+//! ```
+//!
+//! The supported operations are
+//!
+//! - primitives: `(sphere radius)`, `(cube x y z)`, `(cylinder radius height)`,
+//!   `(torus major minor)`, `(capsule ax ay az bx by bz radius)`,
+//!   `(cone height bottom-radius top-radius)`, `(rounded-box x y z radius)`,
+//!   `(hexagonal-prism radius height)`, `(triangular-prism radius height)`,
+//!   `(ellipsoid x y z)`, `(octahedron size)` and
+//!   `(half-space nx ny nz offset min-x min-y min-z max-x max-y max-z)`
+//! - fractals: `(mandelbulb power iterations)` where `power` is greater than
+//!   1, `(menger-sponge iterations)` and `(sierpinski-tetrahedron iterations)`
+//! - combinators: `(union a b ...)`, `(intersection a b ...)`,
+//!   `(difference a b ...)` and their smooth versions
+//!   `(smooth-union blend a b ...)`, `(smooth-intersection blend a b ...)` and
+//!   `(smooth-difference blend a b ...)` where `blend` is either
+//!   `(polynomial radius)` or `(exponential radius)`
+//! - transformations: `(translate x y z a)` and `(rotate x y z degrees a)`
+//! - operators: `(repeat sx sy sz nx ny nz a)`, `(mirror x|y|z a)`,
+//!   `(twist rate a)`, `(bend rate a)`, `(elongate x y z a)`,
+//!   `(round radius a)` and `(onion thickness a)`
+//!
+//! [0]: https://en.wikipedia.org/wiki/S-expression
+
+use std::{fs, io, path::Path};
+
+use geo::{mat4::Mat4, Aabb, Axis, Vec3};
+
+use super::*;
+
+/// Result type returned by `parse` and `load`.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Possible errors while parsing a CSG shape.
+#[derive(Debug)]
+pub enum Error {
+    /// The input ended while an expression was still open.
+    UnexpectedEof,
+
+    /// A token that wasn't expected at that position, like an unbalanced
+    /// closing parenthesis or a number where an expression was expected.
+    UnexpectedToken(String),
+
+    /// The name of the operation is not supported.
+    UnknownOperation(String),
+
+    /// The operation was called with the wrong number or kind of arguments.
+    BadArguments(String),
+
+    /// Error while parsing a number.
+    InvalidNumber(String),
+
+    /// IO error.
+    IoError(io::Error),
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::IoError(e)
+    }
+}
+
+/// Load the shape described in the file at the given path.
+pub fn load(path: impl AsRef<Path>) -> Result<BoxedSdf> {
+    parse(&fs::read_to_string(path)?)
+}
+
+/// Parse a shape described by a single expression.
+pub fn parse(input: &str) -> Result<BoxedSdf> {
+    let mut tokens = tokenize(input).into_iter();
+
+    let expr = parse_expr(&mut tokens)?;
+    if let Some(t) = tokens.next() {
+        return Err(Error::UnexpectedToken(t.to_string()));
+    }
+
+    build(&expr)
+}
+
+#[derive(Debug, PartialEq)]
+enum Expr<'a> {
+    Atom(&'a str),
+    List(Vec<Expr<'a>>),
+}
+
+fn tokenize(input: &str) -> Vec<&str> {
+    let mut tokens = vec![];
+
+    for line in input.lines() {
+        let line = line.split(';').next().unwrap_or_default();
+
+        let mut start = None;
+        for (i, c) in line.char_indices() {
+            if c == '(' || c == ')' || c.is_whitespace() {
+                if let Some(s) = start.take() {
+                    tokens.push(&line[s..i]);
+                }
+                if !c.is_whitespace() {
+                    tokens.push(&line[i..=i]);
+                }
+            } else if start.is_none() {
+                start = Some(i);
+            }
+        }
+
+        if let Some(s) = start {
+            tokens.push(&line[s..]);
+        }
+    }
+
+    tokens
+}
+
+fn parse_expr<'a>(tokens: &mut impl Iterator<Item = &'a str>) -> Result<Expr<'a>> {
+    match tokens.next().ok_or(Error::UnexpectedEof)? {
+        "(" => {
+            let mut list = vec![];
+            loop {
+                match parse_expr(tokens) {
+                    Ok(e) => list.push(e),
+                    Err(Error::UnexpectedToken(t)) if t == ")" => return Ok(Expr::List(list)),
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+        ")" => Err(Error::UnexpectedToken(")".to_string())),
+        atom => Ok(Expr::Atom(atom)),
+    }
+}
+
+/// The arguments of an operation that are consumed while building it.
+struct Args<'e, 'a> {
+    op: &'a str,
+    args: std::slice::Iter<'e, Expr<'a>>,
+}
+
+impl<'e, 'a> Args<'e, 'a> {
+    fn bad_arguments(&self) -> Error {
+        Error::BadArguments(self.op.to_string())
+    }
+
+    fn atom(&mut self) -> Result<&'a str> {
+        match self.args.next() {
+            Some(Expr::Atom(a)) => Ok(a),
+            _ => Err(self.bad_arguments()),
+        }
+    }
+
+    fn number(&mut self) -> Result<f64> {
+        parse_number(self.atom()?)
+    }
+
+    fn count(&mut self) -> Result<u32> {
+        let a = self.atom()?;
+        a.parse().map_err(|_| Error::InvalidNumber(a.to_string()))
+    }
+
+    fn vec3(&mut self) -> Result<Vec3> {
+        Ok(Vec3::new(self.number()?, self.number()?, self.number()?))
+    }
+
+    fn axis(&mut self) -> Result<Axis> {
+        match self.atom()? {
+            "x" => Ok(Axis::X),
+            "y" => Ok(Axis::Y),
+            "z" => Ok(Axis::Z),
+            _ => Err(self.bad_arguments()),
+        }
+    }
+
+    fn blend(&mut self) -> Result<Blend> {
+        let args = match self.args.next() {
+            Some(Expr::List(l)) => l,
+            _ => return Err(self.bad_arguments()),
+        };
+
+        match args.as_slice() {
+            [Expr::Atom("polynomial"), Expr::Atom(k)] => Ok(Blend::Polynomial(parse_number(k)?)),
+            [Expr::Atom("exponential"), Expr::Atom(k)] => Ok(Blend::Exponential(parse_number(k)?)),
+            _ => Err(self.bad_arguments()),
+        }
+    }
+
+    fn shape(&mut self) -> Result<BoxedSdf> {
+        match self.args.next() {
+            Some(e @ Expr::List(_)) => build(e),
+            _ => Err(self.bad_arguments()),
+        }
+    }
+
+    /// Fold all the remaining arguments, that must be at least two shapes.
+    fn fold(&mut self, f: impl Fn(BoxedSdf, BoxedSdf) -> BoxedSdf) -> Result<BoxedSdf> {
+        let mut acc = f(self.shape()?, self.shape()?);
+        while self.args.len() > 0 {
+            acc = f(acc, self.shape()?);
+        }
+
+        Ok(acc)
+    }
+
+    fn finish(mut self) -> Result<()> {
+        match self.args.next() {
+            None => Ok(()),
+            Some(_) => Err(self.bad_arguments()),
+        }
+    }
+}
+
+fn build(expr: &Expr) -> Result<BoxedSdf> {
+    let (op, args) = match expr {
+        Expr::List(l) => match l.split_first() {
+            Some((Expr::Atom(op), args)) => (*op, args),
+            Some((Expr::List(_), _)) => return Err(Error::UnexpectedToken("(".to_string())),
+            None => return Err(Error::UnexpectedToken(")".to_string())),
+        },
+        Expr::Atom(a) => return Err(Error::UnexpectedToken(a.to_string())),
+    };
+
+    let mut args = Args {
+        op,
+        args: args.iter(),
+    };
+
+    let sdf: BoxedSdf = match op {
+        "sphere" => Box::new(Sphere::new(args.number()?)),
+        "cube" => Box::new(Cube::new(args.vec3()?)),
+        "cylinder" => Box::new(Cylinder::new(args.number()?, args.number()?)),
+        "torus" => Box::new(Torus::new(args.number()?, args.number()?)),
+        "capsule" => Box::new(Capsule::new(args.vec3()?, args.vec3()?, args.number()?)),
+        "cone" => Box::new(Cone::new(args.number()?, args.number()?, args.number()?)),
+        "rounded-box" => Box::new(RoundedBox::new(args.vec3()?, args.number()?)),
+        "hexagonal-prism" => Box::new(HexagonalPrism::new(args.number()?, args.number()?)),
+        "triangular-prism" => Box::new(TriangularPrism::new(args.number()?, args.number()?)),
+        "ellipsoid" => Box::new(Ellipsoid::new(args.vec3()?)),
+        "octahedron" => Box::new(Octahedron::new(args.number()?)),
+        "half-space" => {
+            let normal = args.vec3()?;
+            let offset = args.number()?;
+            let bounds = Aabb::new(args.vec3()?).expanded(args.vec3()?);
+
+            Box::new(HalfSpace::new(normal, offset, bounds))
+        }
+
+        "mandelbulb" => {
+            let power = args.number()?;
+            if power.is_nan() || power <= 1.0 {
+                return Err(args.bad_arguments());
+            }
+
+            Box::new(Mandelbulb::new(power, args.count()?))
+        }
+        "menger-sponge" => Box::new(MengerSponge::new(args.count()?)),
+        "sierpinski-tetrahedron" => Box::new(SierpinskiTetrahedron::new(args.count()?)),
+
+        "union" => args.fold(|a, b| Box::new(a.union(b)))?,
+        "intersection" => args.fold(|a, b| Box::new(a.intersection(b)))?,
+        "difference" => args.fold(|a, b| Box::new(a.difference(b)))?,
+        "smooth-union" => {
+            let blend = args.blend()?;
+            args.fold(|a, b| Box::new(a.smooth_union(b, blend)))?
+        }
+        "smooth-intersection" => {
+            let blend = args.blend()?;
+            args.fold(|a, b| Box::new(a.smooth_intersection(b, blend)))?
+        }
+        "smooth-difference" => {
+            let blend = args.blend()?;
+            args.fold(|a, b| Box::new(a.smooth_difference(b, blend)))?
+        }
+
+        "translate" => {
+            let v = args.vec3()?;
+            Box::new(args.shape()?.transformed(Mat4::translate(v)))
+        }
+        "rotate" => {
+            let axis = args.vec3()?;
+            let angle = args.number()?.to_radians();
+            Box::new(args.shape()?.transformed(Mat4::rotate(axis, angle)))
+        }
+
+        "repeat" => {
+            let spacing = args.vec3()?;
+            let counts = [args.count()?, args.count()?, args.count()?];
+            Box::new(args.shape()?.repeated(spacing, counts))
+        }
+        "mirror" => {
+            let axis = args.axis()?;
+            Box::new(args.shape()?.mirrored(axis))
+        }
+        "twist" => {
+            let rate = args.number()?;
+            Box::new(args.shape()?.twisted(rate))
+        }
+        "bend" => {
+            let rate = args.number()?;
+            Box::new(args.shape()?.bent(rate))
+        }
+        "elongate" => {
+            let amount = args.vec3()?;
+            Box::new(args.shape()?.elongated(amount))
+        }
+        "round" => {
+            let radius = args.number()?;
+            Box::new(args.shape()?.rounded(radius))
+        }
+        "onion" => {
+            let thickness = args.number()?;
+            Box::new(args.shape()?.onion(thickness))
+        }
+
+        _ => return Err(Error::UnknownOperation(op.to_string())),
+    };
+
+    args.finish()?;
+
+    Ok(sdf)
+}
+
+fn parse_number(a: &str) -> Result<f64> {
+    a.parse().map_err(|_| Error::InvalidNumber(a.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let sdf = parse(
+            "; a rounded cube with a hole
+            (difference
+              (intersection (sphere 0.65) (cube 1 1 1))
+              (rotate 1 0 0 90 (cylinder 0.25 1.1)) ; along Z
+              (cylinder 0.25 1.1))",
+        )
+        .unwrap();
+
+        let expected = Sphere::new(0.65)
+            .intersection(Cube::new(Vec3::replicate(1.0)))
+            .difference(Cylinder::new(0.25, 1.1).transformed(Mat4::rotate(
+                Vec3::new(1.0, 0.0, 0.0),
+                90.0_f64.to_radians(),
+            )))
+            .difference(Cylinder::new(0.25, 1.1));
+
+        assert_eq!(sdf.bbox(), expected.bbox());
+        for p in [
+            Vec3::zero(),
+            Vec3::new(0.4, 0.4, 0.0),
+            Vec3::new(0.0, 0.0, 0.45),
+            Vec3::new(1.0, -0.2, 0.3),
+        ] {
+            assert_eq!(sdf.dist(&p), expected.dist(&p));
+        }
+
+        let sdf = parse("(smooth-union (polynomial 0.4) (translate -1 0 0 (sphere 1)) (translate 1 0 0 (sphere 1)))").unwrap();
+        assert!((sdf.dist(&Vec3::zero()) + 0.1).abs() < 1e-9);
+
+        let sdf = parse("(mirror x (repeat 2 0 0 3 1 1 (round 0.1 (octahedron 0.5))))").unwrap();
+        assert!(sdf.dist(&Vec3::new(-4.0, 0.0, 0.0)) < 0.0);
+
+        let sdf = parse("(translate 0 0 1 (menger-sponge 3))").unwrap();
+        let p = Vec3::new(0.3, 0.2, 1.9);
+        assert_eq!(
+            sdf.dist(&p),
+            MengerSponge::new(3).dist(&(p - Vec3::new(0.0, 0.0, 1.0)))
+        );
+        assert!(sdf.orbit_trap(&p).is_some());
+    }
+
+    #[test]
+    fn test_load() {
+        let data = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("..")
+            .join("data");
+
+        let rook = load(data.join("rook.csg")).unwrap();
+        assert!(rook.dist(&Vec3::new(0.0, 1.0, 0.0)) < 0.0);
+        assert!(rook.dist(&Vec3::new(0.0, 1.8, 0.0)) > 0.0);
+
+        assert!(matches!(
+            load(data.join("missing.csg")),
+            Err(Error::IoError(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(parse("(sphere 1"), Err(Error::UnexpectedEof)));
+        assert!(matches!(parse("(sphere 1))"), Err(Error::UnexpectedToken(t)) if t == ")"));
+        assert!(matches!(parse("1"), Err(Error::UnexpectedToken(t)) if t == "1"));
+        assert!(matches!(parse("()"), Err(Error::UnexpectedToken(_))));
+        assert!(matches!(parse("(sphere one)"), Err(Error::InvalidNumber(n)) if n == "one"));
+        assert!(matches!(parse("(blob 1)"), Err(Error::UnknownOperation(op)) if op == "blob"));
+        assert!(matches!(parse("(sphere 1 2)"), Err(Error::BadArguments(op)) if op == "sphere"));
+        assert!(
+            matches!(parse("(union (sphere 1))"), Err(Error::BadArguments(op)) if op == "union")
+        );
+        assert!(matches!(
+            parse("(smooth-union (cubic 1) (sphere 1) (sphere 2))"),
+            Err(Error::BadArguments(_))
+        ));
+        assert!(
+            matches!(parse("(mandelbulb 1 5)"), Err(Error::BadArguments(op)) if op == "mandelbulb")
+        );
+        assert!(matches!(
+            parse("(mandelbulb nan 5)"),
+            Err(Error::BadArguments(_))
+        ));
+    }
+}
@@ -1,6 +1,6 @@
 use geo::Aabb;
 
-use crate::{Hit, Ray, Shape, Surface, Vec3};
+use crate::{Boundary, Hit, Ray, Shape, Solid, Span, Surface, Vec3};
 
 #[derive(Debug, Clone)]
 pub struct CubeGeometry {
@@ -57,3 +57,15 @@ impl Surface for CubeGeometry {
         Vec3::new(0.0, 1.0, 0.0)
     }
 }
+
+impl Solid for CubeGeometry {
+    fn spans(&self, ray: &Ray) -> Vec<Span> {
+        match self.bbox.ray_intersection(ray) {
+            Some((tmin, tmax)) if tmin < tmax => {
+                let boundary = |t| Boundary::new(t, self.normal_at(ray.point_at(t)));
+                vec![Span::new(boundary(tmin), boundary(tmax))]
+            }
+            _ => vec![],
+        }
+    }
+}
@@ -0,0 +1,311 @@
+use geo::{
+    primitive::polyline::Polyline,
+    ray::Ray,
+    spatial_index::{Bvh, Shape},
+    sphere, Aabb, Vec3,
+};
+
+use crate::{ConeGeometry, Hit, Surface};
+
+/// How the strands of a `CurvesGeometry` are shaped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CurveKind {
+    /// Flat strips that always face the incoming ray. They're the cheapest
+    /// option and are good enough for thin strands like hair, fur and grass.
+    Ribbon,
+
+    /// Round tubes with spherical joints, useful for thick strands that are
+    /// seen up close like wires.
+    Tube,
+}
+
+/// A single strand made of a series of points, each with its own radius.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Curve {
+    points: Vec<Vec3>,
+    radii: Vec<f64>,
+}
+
+impl Curve {
+    /// Create a `Curve` that goes through all the `points` where the radius at
+    /// each point is given by the respective element of `radii`.
+    pub fn new(points: Vec<Vec3>, radii: Vec<f64>) -> Self {
+        assert_eq!(points.len(), radii.len());
+
+        Curve { points, radii }
+    }
+
+    /// Create a `Curve` that follows the given `Polyline` and whose radius
+    /// goes linearly from `root_radius` at the first point to `tip_radius` at
+    /// the last one.
+    pub fn from_polyline(polyline: &Polyline, (root_radius, tip_radius): (f64, f64)) -> Self {
+        let length = polyline.norm();
+
+        let mut l = 0.0;
+        let mut prev = polyline.points.first().cloned();
+        let radii = polyline
+            .iter()
+            .map(|p| {
+                l += prev.map_or(0.0, |prev| prev.dist(p));
+                prev = Some(p);
+
+                let t = if length > 0.0 { l / length } else { 0.0 };
+                root_radius + (tip_radius - root_radius) * t
+            })
+            .collect();
+
+        Curve::new(polyline.points.clone(), radii)
+    }
+
+    /// Create a `Curve` that approximates the cubic Bézier with the given
+    /// control points with `segments` straight segments. The radius goes
+    /// linearly from `root_radius` to `tip_radius`.
+    pub fn bezier(
+        [p0, p1, p2, p3]: [Vec3; 4],
+        (root_radius, tip_radius): (f64, f64),
+        segments: usize,
+    ) -> Self {
+        assert!(segments > 0);
+
+        let (points, radii) = (0..=segments)
+            .map(|i| {
+                let t = i as f64 / segments as f64;
+                let s = 1.0 - t;
+
+                let p = p0 * s.powi(3)
+                    + p1 * (3.0 * s.powi(2) * t)
+                    + p2 * (3.0 * s * t.powi(2))
+                    + p3 * t.powi(3);
+
+                (p, root_radius + (tip_radius - root_radius) * t)
+            })
+            .unzip();
+
+        Curve::new(points, radii)
+    }
+}
+
+/// A collection of `Curve`s, for example all the hairs of a head or the blades
+/// of grass of a lawn. All the segments of all the curves are stored in a
+/// single `Bvh` so that thousands of strands can be rendered efficiently.
+///
+/// The index of the `Curve` that was hit is reported in `Hit::primitive`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CurvesGeometry {
+    segments: Bvh<CurveSegment>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+struct CurveSegment {
+    kind: CurveKind,
+    curve: usize,
+    p0: Vec3,
+    p1: Vec3,
+    r0: f64,
+    r1: f64,
+}
+
+impl CurvesGeometry {
+    pub fn new(kind: CurveKind, curves: impl IntoIterator<Item = Curve>) -> Self {
+        let mut segments = vec![];
+        for (curve, c) in curves.into_iter().enumerate() {
+            for (p, r) in c.points.windows(2).zip(c.radii.windows(2)) {
+                if p[0] == p[1] {
+                    continue;
+                }
+
+                segments.push(CurveSegment {
+                    kind,
+                    curve,
+                    p0: p[0],
+                    p1: p[1],
+                    r0: r[0],
+                    r1: r[1],
+                });
+            }
+        }
+
+        CurvesGeometry {
+            segments: segments.into_iter().collect(),
+        }
+    }
+}
+
+impl Shape for CurvesGeometry {
+    type Intersection = Hit;
+
+    fn intersection(&self, ray: &Ray) -> Option<Self::Intersection> {
+        let (_, hit) = self
+            .segments
+            .intersections(ray)
+            .min_by(|(_, h0), (_, h1)| h0.t.partial_cmp(&h1.t).unwrap())?;
+
+        Some(hit)
+    }
+
+    fn bbox(&self) -> Aabb {
+        self.segments
+            .bbox()
+            .unwrap_or_else(|| Aabb::new(Vec3::zero()))
+    }
+}
+
+impl Surface for CurvesGeometry {
+    fn normal_at(&self, _p: Vec3) -> Vec3 {
+        unreachable!()
+    }
+}
+
+impl CurveSegment {
+    fn ribbon_intersection(&self, ray: &Ray) -> Option<(f64, Vec3)> {
+        let v = self.p1 - self.p0;
+        let w = ray.origin - self.p0;
+
+        // closest points between the line of the ray and the segment
+        let (a, b, c) = (ray.dir.norm2(), ray.dir.dot(v), v.norm2());
+        let (d, e) = (ray.dir.dot(w), v.dot(w));
+
+        let den = a * c - b * b;
+        if den < 1e-12 * a * c {
+            return None;
+        }
+
+        let s = ((a * e - b * d) / den).clamp(0.0, 1.0);
+        let q = self.p0 + v * s;
+        let t = (q - ray.origin).dot(ray.dir) / a;
+        if t < 1e-6 {
+            return None;
+        }
+
+        let radius = self.r0 + (self.r1 - self.r0) * s;
+        let offset = ray.point_at(t) - q;
+        let dist = offset.norm();
+        if dist > radius {
+            return None;
+        }
+
+        // rays leaving the ribbon would hit it again since it always faces the
+        // ray
+        if ray.origin.segment_dist(self.p0, self.p1) < self.r0.max(self.r1) {
+            return None;
+        }
+
+        // bend the normal across the width so that the ribbon is shaded like a
+        // tube
+        let tangent = v / c.sqrt();
+        let facing = -ray.dir.normalized();
+        let facing = (facing - tangent * facing.dot(tangent)).normalized();
+        let side = offset - tangent * offset.dot(tangent);
+        if dist <= 0.0 || side.norm2() <= 0.0 {
+            return Some((t, facing));
+        }
+
+        let u = (dist / radius).min(1.0);
+        let n = facing * (1.0 - u.powi(2)).sqrt() + side.normalized() * u;
+
+        Some((t, n.normalized()))
+    }
+
+    fn tube_intersection(&self, ray: &Ray) -> Option<(f64, Vec3)> {
+        let side = ConeGeometry::new(self.p0, self.r0, self.p1, self.r1);
+
+        let mut closest = side
+            .side_intersection(ray)
+            .map(|t| (t, side.normal_at(ray.point_at(t))));
+
+        for (c, r) in [(self.p0, self.r0), (self.p1, self.r1)] {
+            let t = match sphere::ray_intersection(c, r, ray) {
+                Some(t) if t > 1e-6 => t,
+                _ => continue,
+            };
+
+            if t < closest.map_or(f64::INFINITY, |(ct, _)| ct) {
+                closest = Some((t, sphere::normal(c, ray.point_at(t))));
+            }
+        }
+
+        closest
+    }
+}
+
+impl Shape for CurveSegment {
+    type Intersection = Hit;
+
+    fn intersection(&self, ray: &Ray) -> Option<Self::Intersection> {
+        let (t, n) = match self.kind {
+            CurveKind::Ribbon => self.ribbon_intersection(ray)?,
+            CurveKind::Tube => self.tube_intersection(ray)?,
+        };
+
+        let mut hit = Hit::new(t, Some((ray.point_at(t), n)));
+        hit.primitive = self.curve;
+
+        Some(hit)
+    }
+
+    fn bbox(&self) -> Aabb {
+        sphere::bounding_box(self.p0, self.r0).union(&sphere::bounding_box(self.p1, self.r1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intersection() {
+        let curves = vec![
+            Curve::new(
+                vec![
+                    Vec3::zero(),
+                    Vec3::new(0.0, 0.0, 1.0),
+                    Vec3::new(0.0, 0.0, 2.0),
+                ],
+                vec![0.1, 0.1, 0.1],
+            ),
+            Curve::bezier(
+                [
+                    Vec3::new(0.0, 5.0, 0.0),
+                    Vec3::new(0.0, 5.0, 1.0),
+                    Vec3::new(1.0, 5.0, 2.0),
+                    Vec3::new(2.0, 5.0, 2.0),
+                ],
+                (0.2, 0.0),
+                8,
+            ),
+        ];
+
+        for kind in [CurveKind::Ribbon, CurveKind::Tube] {
+            let geom = CurvesGeometry::new(kind, curves.clone());
+
+            let r = Ray::new(Vec3::new(-5.0, 0.0, 0.5), Vec3::new(1.0, 0.0, 0.0));
+            let hit = geom.intersection(&r).unwrap();
+            assert_eq!(hit.primitive, 0);
+
+            let (p, n) = hit.point_and_normal.unwrap();
+            assert!(p.dist(Vec3::new(-0.1, 0.0, 0.5)) < 0.1 + 1e-9);
+            assert!(n.dist(Vec3::new(-1.0, 0.0, 0.0)) < 1e-9);
+
+            // the tip of the bezier is thinner than the root
+            let r = Ray::new(Vec3::new(0.0, 1.0, 0.1), Vec3::new(0.0, 1.0, 0.0));
+            assert_eq!(geom.intersection(&r).unwrap().primitive, 1);
+            let r = Ray::new(Vec3::new(1.95, 0.0, 2.19), Vec3::new(0.0, 1.0, 0.0));
+            assert!(geom.intersection(&r).is_none());
+
+            let r = Ray::new(Vec3::new(-5.0, 0.0, 3.0), Vec3::new(1.0, 0.0, 0.0));
+            assert!(geom.intersection(&r).is_none());
+        }
+    }
+
+    #[test]
+    fn test_from_polyline() {
+        let polyline = Polyline::from(vec![
+            Vec3::zero(),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(1.0, 3.0, 0.0),
+        ]);
+
+        let curve = Curve::from_polyline(&polyline, (1.0, 0.0));
+        assert_eq!(curve.radii, vec![1.0, 0.75, 0.0]);
+    }
+}
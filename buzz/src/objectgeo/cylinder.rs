@@ -1,6 +1,6 @@
 use geo::Aabb;
 
-use crate::{Hit, Ray, Shape, Surface, Vec3};
+use crate::{Boundary, ConeGeometry, Hit, Ray, Shape, Solid, Span, Surface, Vec3};
 
 /// Cylinder positioned at the origin going through the Z axis.
 #[derive(Debug, Clone)]
@@ -69,6 +69,103 @@ impl Shape for CylinderGeometry {
 impl Surface for CylinderGeometry {
     fn normal_at(&self, mut p: Vec3) -> Vec3 {
         p.z = 0.0;
-        p
+        p.normalized()
+    }
+}
+
+/// As a `Solid` the cylinder is closed by two caps at `zmin` and `zmax`.
+impl Solid for CylinderGeometry {
+    fn spans(&self, ray: &Ray) -> Vec<Span> {
+        let unbounded = (
+            Boundary::new(f64::NEG_INFINITY, Vec3::zero()),
+            Boundary::new(f64::INFINITY, Vec3::zero()),
+        );
+
+        // span inside the infinite cylinder
+        let a = ray.dir.x.powi(2) + ray.dir.y.powi(2);
+        let b = ray.origin.x * ray.dir.x + ray.origin.y * ray.dir.y;
+        let c = ray.origin.x.powi(2) + ray.origin.y.powi(2) - self.radius.powi(2);
+
+        let side = if a == 0.0 {
+            if c >= 0.0 {
+                return vec![];
+            }
+            unbounded
+        } else {
+            let discr = b.powi(2) - a * c;
+            if discr <= 0.0 {
+                return vec![];
+            }
+
+            let boundary = |t| Boundary::new(t, self.normal_at(ray.point_at(t)));
+            (
+                boundary((-b - discr.sqrt()) / a),
+                boundary((-b + discr.sqrt()) / a),
+            )
+        };
+
+        // span between the planes of the caps
+        let caps = if ray.dir.z == 0.0 {
+            if ray.origin.z < self.zmin || ray.origin.z > self.zmax {
+                return vec![];
+            }
+            unbounded
+        } else {
+            let bottom = Boundary::new(
+                (self.zmin - ray.origin.z) / ray.dir.z,
+                Vec3::new(0.0, 0.0, -1.0),
+            );
+            let top = Boundary::new(
+                (self.zmax - ray.origin.z) / ray.dir.z,
+                Vec3::new(0.0, 0.0, 1.0),
+            );
+
+            if ray.dir.z > 0.0 {
+                (bottom, top)
+            } else {
+                (top, bottom)
+            }
+        };
+
+        let enter = if side.0.t > caps.0.t { side.0 } else { caps.0 };
+        let exit = if side.1.t < caps.1.t { side.1 } else { caps.1 };
+        if enter.t >= exit.t {
+            return vec![];
+        }
+
+        vec![Span::new(enter, exit)]
+    }
+}
+
+/// A closed cylinder of the given `radius` going from `start` to `end`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CappedCylinderGeometry {
+    // a cylinder is just a cone frustum where both the radii are the same
+    frustum: ConeGeometry,
+}
+
+impl CappedCylinderGeometry {
+    pub fn new(start: Vec3, end: Vec3, radius: f64) -> Self {
+        CappedCylinderGeometry {
+            frustum: ConeGeometry::new(start, radius, end, radius),
+        }
+    }
+}
+
+impl Shape for CappedCylinderGeometry {
+    type Intersection = Hit;
+
+    fn intersection(&self, ray: &Ray) -> Option<Self::Intersection> {
+        self.frustum.intersection(ray)
+    }
+
+    fn bbox(&self) -> Aabb {
+        self.frustum.bbox()
+    }
+}
+
+impl Surface for CappedCylinderGeometry {
+    fn normal_at(&self, p: Vec3) -> Vec3 {
+        self.frustum.normal_at(p)
     }
 }
@@ -0,0 +1,180 @@
+use std::f64::consts::PI;
+
+use geo::{plane, ray::Ray, spatial_index::Shape, Aabb, Vec3};
+
+use super::cone::disc_bbox;
+use crate::{sampler::Sampler, Hit, Surface};
+
+/// A flat disk with the given `center`, `radius` and facing towards `normal`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct DiskGeometry {
+    center: Vec3,
+    normal: Vec3,
+    radius: f64,
+
+    // orthonormal vectors on the plane of the disk used for the uv mapping
+    tangent: Vec3,
+    bitangent: Vec3,
+}
+
+impl DiskGeometry {
+    pub fn new(center: Vec3, normal: Vec3, radius: f64) -> Self {
+        let normal = normal.normalized();
+
+        let helper = if normal.x.abs() > 0.9 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let tangent = helper.cross(normal).normalized();
+        let bitangent = normal.cross(tangent);
+
+        DiskGeometry {
+            center,
+            normal,
+            radius,
+            tangent,
+            bitangent,
+        }
+    }
+
+    /// Return the polar coordinates of the given point on the plane of the disk
+    /// as the distance from the center relative to the radius and the angle
+    /// relative to a full turn. Points inside the disk have both coordinates
+    /// in [0, 1].
+    pub fn uv_at(&self, p: Vec3) -> (f64, f64) {
+        let d = p - self.center;
+
+        let (x, y) = (d.dot(self.tangent), d.dot(self.bitangent));
+        let angle = y.atan2(x);
+
+        (
+            (x.powi(2) + y.powi(2)).sqrt() / self.radius,
+            if angle < 0.0 { angle + 2.0 * PI } else { angle } / (2.0 * PI),
+        )
+    }
+}
+
+impl Shape for DiskGeometry {
+    type Intersection = Hit;
+
+    fn bbox(&self) -> Aabb {
+        disc_bbox(self.center, self.normal, self.radius)
+    }
+
+    fn bounding_sphere(&self) -> (Vec3, f64) {
+        (self.center, self.radius)
+    }
+
+    fn intersection(&self, ray: &Ray) -> Option<Self::Intersection> {
+        let t = plane::intersection(self.center, self.normal, ray)?;
+
+        if ray.point_at(t).dist2(self.center) > self.radius.powi(2) {
+            return None;
+        }
+
+        Some(Hit::new(t, None))
+    }
+}
+
+impl Surface for DiskGeometry {
+    fn normal_at(&self, _pt: Vec3) -> Vec3 {
+        self.normal
+    }
+
+    fn area(&self) -> Option<f64> {
+        Some(PI * self.radius.powi(2))
+    }
+
+    fn sample(&self, sampler: &mut dyn Sampler) -> Option<Vec3> {
+        let (a, b) = sampler.next_2d();
+
+        let r = a.sqrt() * self.radius;
+        let theta = b * 2.0 * PI;
+
+        Some(self.center + self.tangent * (r * theta.cos()) + self.bitangent * (r * theta.sin()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_xorshift::XorShiftRng;
+
+    use super::*;
+
+    #[test]
+    fn test_intersection() {
+        let disk = DiskGeometry::new(Vec3::new(1.0, 2.0, 3.0), Vec3::new(0.0, 0.0, 2.0), 2.0);
+
+        assert_eq!(disk.normal_at(Vec3::zero()), Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(disk.area(), Some(4.0 * PI));
+        assert_eq!(disk.bounding_sphere(), (Vec3::new(1.0, 2.0, 3.0), 2.0));
+
+        let hit = disk
+            .intersection(&Ray::new(
+                Vec3::new(2.0, 3.0, 5.0),
+                Vec3::new(0.0, 0.0, -1.0),
+            ))
+            .unwrap();
+        assert_eq!(hit.t, 2.0);
+
+        // inside the bounding box, but outside of the disk
+        assert!(disk
+            .intersection(&Ray::new(
+                Vec3::new(2.5, 3.5, 5.0),
+                Vec3::new(0.0, 0.0, -1.0),
+            ))
+            .is_none());
+
+        // parallel to the disk
+        assert!(disk
+            .intersection(&Ray::new(
+                Vec3::new(1.0, 2.0, 3.0),
+                Vec3::new(1.0, 0.0, 0.0),
+            ))
+            .is_none());
+    }
+
+    #[test]
+    fn test_uv() {
+        let disk = DiskGeometry::new(Vec3::new(1.0, 2.0, 3.0), Vec3::new(0.0, 0.0, 1.0), 2.0);
+
+        let (u, v) = disk.uv_at(Vec3::new(1.0, 2.0, 3.0));
+        assert_eq!(u, 0.0);
+        assert!((0.0..1.0).contains(&v));
+
+        // a quarter turn from the tangent on the rim
+        let p = Vec3::new(1.0, 2.0, 3.0) + disk.bitangent * 2.0;
+        let (u, v) = disk.uv_at(p);
+        assert!((u - 1.0).abs() < 1e-9);
+        assert!((v - 0.25).abs() < 1e-9);
+
+        let (u, v) = disk.uv_at(Vec3::new(1.0, 2.0, 3.0) - disk.tangent);
+        assert!((u - 0.5).abs() < 1e-9);
+        assert!((v - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_sample() {
+        let disk = DiskGeometry::new(Vec3::new(1.0, 2.0, 3.0), Vec3::new(1.0, 1.0, 0.0), 2.0);
+        let mut rng = XorShiftRng::seed_from_u64(0);
+
+        let n = 4000;
+        let mut inner = 0;
+        for _ in 0..n {
+            let p = disk.sample(&mut rng).unwrap();
+
+            assert!((p - Vec3::new(1.0, 2.0, 3.0)).dot(disk.normal).abs() < 1e-9);
+            assert!(p.dist(Vec3::new(1.0, 2.0, 3.0)) <= 2.0 + 1e-9);
+
+            if p.dist(Vec3::new(1.0, 2.0, 3.0)) < 1.0 {
+                inner += 1;
+            }
+        }
+
+        // the samples are uniform over the area, hence a quarter of them is in
+        // the inner disk of half the radius
+        assert!((900..1100).contains(&inner), "{}", inner);
+    }
+}
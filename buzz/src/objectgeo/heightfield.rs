@@ -0,0 +1,285 @@
+use image::GrayImage;
+
+use geo::{ray::Ray, spatial_index::Shape, Aabb, Triangle, Vec3};
+
+use crate::{Hit, Surface};
+
+/// A terrain described by a regular grid of heights over the XY plane. Each
+/// cell of the grid is split in two triangles, but the normals are smoothly
+/// interpolated between the vertices.
+///
+/// The intersection walks only the cells crossed by the ray, hence it's way
+/// faster and lighter than building a facet for each triangle.
+#[derive(Debug, PartialEq, Clone)]
+pub struct HeightfieldGeometry {
+    start: (f64, f64),
+    cell_size: (f64, f64),
+
+    // number of samples along x and y
+    nx: usize,
+    ny: usize,
+
+    // heights and normals of the samples stored in row major order
+    heights: Vec<f64>,
+    normals: Vec<Vec3>,
+
+    bbox: Aabb,
+}
+
+impl HeightfieldGeometry {
+    /// Create a `HeightfieldGeometry` that covers the rectangle between
+    /// `(sx, sy)` and `(ex, ey)` by sampling `fun` on a grid of `steps` x
+    /// `steps` points.
+    pub fn from_fn(
+        (sx, sy): (f64, f64),
+        (ex, ey): (f64, f64),
+        steps: u16,
+        fun: impl Fn(f64, f64) -> f64,
+    ) -> Self {
+        assert!(steps >= 2);
+
+        let steps = usize::from(steps);
+        let mut heights = Vec::with_capacity(steps.pow(2));
+
+        for i in 0..steps {
+            let y = sy + (ey - sy) * (i as f64) / (steps as f64 - 1.0);
+            for j in 0..steps {
+                let x = sx + (ex - sx) * (j as f64) / (steps as f64 - 1.0);
+
+                heights.push(fun(x, y));
+            }
+        }
+
+        HeightfieldGeometry::new((sx, sy), (ex, ey), (steps, steps), heights)
+    }
+
+    /// Create a `HeightfieldGeometry` that covers the rectangle between
+    /// `(sx, sy)` and `(ex, ey)` where the height of each point is given by the
+    /// intensity of the corresponding pixel of the image scaled so that white
+    /// is `max_height`. The top of the image maps to `ey`.
+    pub fn from_image(
+        img: &GrayImage,
+        (sx, sy): (f64, f64),
+        (ex, ey): (f64, f64),
+        max_height: f64,
+    ) -> Self {
+        let (w, h) = img.dimensions();
+        assert!(w >= 2 && h >= 2);
+
+        let mut heights = Vec::with_capacity(w as usize * h as usize);
+        for y in (0..h).rev() {
+            for x in 0..w {
+                let image::Luma([l]) = *img.get_pixel(x, y);
+                heights.push(f64::from(l) / 255.0 * max_height);
+            }
+        }
+
+        HeightfieldGeometry::new((sx, sy), (ex, ey), (w as usize, h as usize), heights)
+    }
+
+    fn new(
+        (sx, sy): (f64, f64),
+        (ex, ey): (f64, f64),
+        (nx, ny): (usize, usize),
+        heights: Vec<f64>,
+    ) -> Self {
+        let (sx, ex) = (sx.min(ex), sx.max(ex));
+        let (sy, ey) = (sy.min(ey), sy.max(ey));
+
+        let cell_size = ((ex - sx) / (nx - 1) as f64, (ey - sy) / (ny - 1) as f64);
+
+        let (zmin, zmax) = heights
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(zmin, zmax), &z| {
+                (zmin.min(z), zmax.max(z))
+            });
+
+        let mut hf = HeightfieldGeometry {
+            start: (sx, sy),
+            cell_size,
+            nx,
+            ny,
+            heights,
+            normals: vec![],
+            bbox: Aabb::new(Vec3::new(sx, sy, zmin)).expanded(Vec3::new(ex, ey, zmax)),
+        };
+
+        // estimate the normals with the central differences of the heights
+        hf.normals = (0..ny)
+            .flat_map(|y| (0..nx).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let (x0, x1) = (x.saturating_sub(1), (x + 1).min(nx - 1));
+                let (y0, y1) = (y.saturating_sub(1), (y + 1).min(ny - 1));
+
+                let dzdx = (hf.height(x1, y) - hf.height(x0, y)) / ((x1 - x0) as f64 * cell_size.0);
+                let dzdy = (hf.height(x, y1) - hf.height(x, y0)) / ((y1 - y0) as f64 * cell_size.1);
+
+                Vec3::new(-dzdx, -dzdy, 1.0).normalized()
+            })
+            .collect();
+
+        hf
+    }
+
+    fn height(&self, x: usize, y: usize) -> f64 {
+        self.heights[y * self.nx + x]
+    }
+
+    fn vertex(&self, x: usize, y: usize) -> Vec3 {
+        Vec3::new(
+            self.start.0 + x as f64 * self.cell_size.0,
+            self.start.1 + y as f64 * self.cell_size.1,
+            self.height(x, y),
+        )
+    }
+
+    /// Intersect the two triangles of the cell `(x, y)` returning the closest
+    /// t parameter.
+    fn cell_intersection(&self, (x, y): (usize, usize), ray: &Ray) -> Option<f64> {
+        let a = self.vertex(x, y);
+        let b = self.vertex(x + 1, y);
+        let c = self.vertex(x, y + 1);
+        let d = self.vertex(x + 1, y + 1);
+
+        [
+            Triangle::new(a, b, d).intersection(ray),
+            Triangle::new(a, d, c).intersection(ray),
+        ]
+        .iter()
+        .flatten()
+        .fold(None, |closest: Option<f64>, &t| match closest {
+            Some(ct) if ct <= t => Some(ct),
+            _ => Some(t),
+        })
+    }
+}
+
+impl Shape for HeightfieldGeometry {
+    type Intersection = Hit;
+
+    fn intersection(&self, ray: &Ray) -> Option<Self::Intersection> {
+        let (t0, t1) = self.bbox.ray_intersection(ray)?;
+        let t0 = t0.max(0.0);
+        if t1 < t0 {
+            return None;
+        }
+
+        let (cx, cy) = (self.nx - 1, self.ny - 1);
+
+        // walk the cells crossed by the ray in order using a 2D DDA, see
+        // "A Fast Voxel Traversal Algorithm for Ray Tracing" by Amanatides and
+        // Woo
+        let p = ray.point_at(t0);
+        let cell = |v: f64, start: f64, size: f64, n: usize| {
+            (((v - start) / size).floor().max(0.0) as usize).min(n - 1)
+        };
+        let mut x = cell(p.x, self.start.0, self.cell_size.0, cx);
+        let mut y = cell(p.y, self.start.1, self.cell_size.1, cy);
+
+        let setup = |cell: usize, origin: f64, dir: f64, start: f64, size: f64| {
+            if dir > 0.0 {
+                let boundary = start + (cell + 1) as f64 * size;
+                ((boundary - origin) / dir, size / dir)
+            } else if dir < 0.0 {
+                let boundary = start + cell as f64 * size;
+                ((boundary - origin) / dir, -size / dir)
+            } else {
+                (f64::INFINITY, f64::INFINITY)
+            }
+        };
+        let (mut tmax_x, tdelta_x) =
+            setup(x, ray.origin.x, ray.dir.x, self.start.0, self.cell_size.0);
+        let (mut tmax_y, tdelta_y) =
+            setup(y, ray.origin.y, ray.dir.y, self.start.1, self.cell_size.1);
+
+        loop {
+            if let Some(t) = self.cell_intersection((x, y), ray) {
+                return Some(Hit::new(t, None));
+            }
+
+            if tmax_x < tmax_y {
+                if tmax_x > t1 {
+                    return None;
+                }
+
+                tmax_x += tdelta_x;
+                if ray.dir.x > 0.0 {
+                    x += 1;
+                    if x >= cx {
+                        return None;
+                    }
+                } else {
+                    x = x.checked_sub(1)?;
+                }
+            } else {
+                if tmax_y > t1 {
+                    return None;
+                }
+
+                tmax_y += tdelta_y;
+                if ray.dir.y > 0.0 {
+                    y += 1;
+                    if y >= cy {
+                        return None;
+                    }
+                } else {
+                    y = y.checked_sub(1)?;
+                }
+            }
+        }
+    }
+
+    fn bbox(&self) -> Aabb {
+        self.bbox.clone()
+    }
+}
+
+impl Surface for HeightfieldGeometry {
+    fn normal_at(&self, p: Vec3) -> Vec3 {
+        // bilinear interpolation of the normals at the corners of the cell
+        let gx = ((p.x - self.start.0) / self.cell_size.0).clamp(0.0, (self.nx - 1) as f64);
+        let gy = ((p.y - self.start.1) / self.cell_size.1).clamp(0.0, (self.ny - 1) as f64);
+
+        let x = (gx.floor() as usize).min(self.nx - 2);
+        let y = (gy.floor() as usize).min(self.ny - 2);
+        let (u, v) = (gx - x as f64, gy - y as f64);
+
+        let n = |x: usize, y: usize| self.normals[y * self.nx + x];
+
+        let bottom = Vec3::lerp(n(x, y), n(x + 1, y), u);
+        let top = Vec3::lerp(n(x, y + 1), n(x + 1, y + 1), u);
+
+        Vec3::lerp(bottom, top, v).normalized()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intersection() {
+        let slope = HeightfieldGeometry::from_fn((0.0, 0.0), (10.0, 10.0), 11, |x, _| x * 0.5);
+
+        let r = Ray::new(Vec3::new(2.5, 3.5, 10.0), Vec3::new(0.0, 0.0, -1.0));
+        let t = slope.intersection(&r).unwrap().t;
+        assert!((t - 8.75).abs() < 1e-9);
+
+        let n = slope.normal_at(r.point_at(t));
+        assert!(n.dist(Vec3::new(-0.5, 0.0, 1.0).normalized()) < 1e-9);
+
+        // grazing ray that crosses many cells before hitting the terrain
+        let r = Ray::new(Vec3::new(-5.0, 9.5, 4.0), Vec3::new(1.0, -0.5, 0.0));
+        let t = slope.intersection(&r).unwrap().t;
+        assert!((t - 13.0).abs() < 1e-9);
+
+        let r = Ray::new(Vec3::new(-5.0, 5.0, 6.0), Vec3::new(1.0, 0.0, 0.0));
+        assert!(slope.intersection(&r).is_none());
+
+        let bumps =
+            HeightfieldGeometry::from_fn((-1.0, -1.0), (1.0, 1.0), 21, |x, y| (x * y * 3.0).sin());
+        let r = Ray::new(Vec3::new(0.5, -0.3, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        let t = bumps.intersection(&r).unwrap().t;
+        assert!((t - (5.0 - (-0.45_f64).sin())).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,282 @@
+//! [Implicit surfaces][0] defined as the zero iso-surface of a scalar field.
+//!
+//! Unlike `SignedDistanceFunction`s the fields don't need to return the
+//! distance to the surface, hence they can't be sphere traced. Instead, the
+//! rays are marched with steps that are safe given the Lipschitz bound of the
+//! field and the exact intersection is found by bisection.
+//!
+//! [0]: https://en.wikipedia.org/wiki/Implicit_surface
+
+use geo::{ray::Ray, spatial_index::Shape, sphere, Aabb, Vec3};
+
+use crate::{Hit, Surface};
+
+/// A scalar field that is negative inside the surface, positive outside and
+/// zero on the surface itself.
+pub trait ImplicitFunction: std::fmt::Debug {
+    /// The value of the field at the given point.
+    fn value(&self, p: &Vec3) -> f64;
+
+    /// The bounding box of the surface, all the points outside of it must have
+    /// a positive value.
+    fn bbox(&self) -> Aabb;
+
+    /// An upper bound of how fast the field changes, that is the [Lipschitz
+    /// constant][0] `L` such that `|f(p) - f(q)| <= L |p - q|` for all `p` and
+    /// `q`. Underestimating it might cause holes in the surface while
+    /// overestimating it slows down the rendering.
+    ///
+    /// [0]: https://en.wikipedia.org/wiki/Lipschitz_continuity
+    fn lipschitz(&self) -> f64;
+
+    /// The gradient of the field at the given point. By default it's estimated
+    /// with central differences.
+    fn gradient(&self, p: &Vec3) -> Vec3 {
+        let e = 1e-6;
+        let Vec3 { x, y, z } = *p;
+
+        Vec3::new(
+            self.value(&Vec3::new(x + e, y, z)) - self.value(&Vec3::new(x - e, y, z)),
+            self.value(&Vec3::new(x, y + e, z)) - self.value(&Vec3::new(x, y - e, z)),
+            self.value(&Vec3::new(x, y, z + e)) - self.value(&Vec3::new(x, y, z - e)),
+        ) / (2.0 * e)
+    }
+}
+
+/// A geometry whose surface is the zero iso-surface of an `ImplicitFunction`.
+#[derive(Debug)]
+pub struct ImplicitGeometry<F> {
+    fun: F,
+}
+
+impl<F: ImplicitFunction> ImplicitGeometry<F> {
+    pub fn new(fun: F) -> Self {
+        ImplicitGeometry { fun }
+    }
+}
+
+impl<F: ImplicitFunction> Surface for ImplicitGeometry<F> {
+    fn normal_at(&self, p: Vec3) -> Vec3 {
+        self.fun.gradient(&p).normalized()
+    }
+}
+
+impl<F: ImplicitFunction> Shape for ImplicitGeometry<F> {
+    type Intersection = Hit;
+
+    fn bbox(&self) -> Aabb {
+        self.fun.bbox()
+    }
+
+    fn intersection(&self, ray: &Ray) -> Option<Self::Intersection> {
+        const MAX_STEPS: usize = 10_000;
+        const MIN_STEP: f64 = 1e-4;
+
+        let (t1, t2) = self.bbox().ray_intersection(ray)?;
+        if t2 < t1 || t2 < 0.0 {
+            return None;
+        }
+
+        // the steps are calculated in world units, hence they must be scaled
+        // wrt the length of the direction
+        let dir_norm = ray.dir.norm();
+        let lipschitz = self.fun.lipschitz() * dir_norm;
+        let min_step = MIN_STEP / dir_norm;
+
+        let mut t = t1.max(min_step);
+        let mut f = self.fun.value(&ray.point_at(t));
+
+        for _ in 0..MAX_STEPS {
+            // the field can't reach zero before `|f| / L`, but force a minimum
+            // step to avoid stalling close to the surface
+            let nt = t + (f.abs() / lipschitz).max(min_step);
+            if nt > t2 {
+                return None;
+            }
+
+            let nf = self.fun.value(&ray.point_at(nt));
+            if (nf < 0.0) != (f < 0.0) {
+                return Some(Hit::new(self.bisect(ray, (t, f), nt), None));
+            }
+
+            t = nt;
+            f = nf;
+        }
+
+        None
+    }
+}
+
+impl<F: ImplicitFunction> ImplicitGeometry<F> {
+    /// Find the root of the field along the ray between `t0` and `t1` given
+    /// that the field has different signs at the two ends.
+    fn bisect(&self, ray: &Ray, (mut t0, f0): (f64, f64), mut t1: f64) -> f64 {
+        for _ in 0..50 {
+            let t = (t0 + t1) / 2.0;
+            let f = self.fun.value(&ray.point_at(t));
+
+            if (f < 0.0) == (f0 < 0.0) {
+                t0 = t;
+            } else {
+                t1 = t;
+            }
+        }
+
+        (t0 + t1) / 2.0
+    }
+}
+
+/// A single blob of `Metaballs`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Metaball {
+    pub center: Vec3,
+
+    /// the radius of influence of the blob, the field is zero past it.
+    pub radius: f64,
+
+    /// how much the blob contributes to the field, negative weights carve the
+    /// other blobs.
+    pub weight: f64,
+}
+
+/// [Metaballs][0] are blobby objects defined by a set of weighted points whose
+/// fields sum up. The surface is where the sum equals the `threshold`.
+///
+/// Each blob uses the compact polynomial falloff `(1 - (d / r)^2)^3` proposed
+/// by Wyvill so that far away blobs don't affect each other.
+///
+/// [0]: https://en.wikipedia.org/wiki/Metaballs
+#[derive(Debug, Clone, PartialEq)]
+pub struct Metaballs {
+    balls: Vec<Metaball>,
+    threshold: f64,
+}
+
+impl Metaballs {
+    pub fn new(threshold: f64) -> Self {
+        assert!(threshold > 0.0);
+
+        Metaballs {
+            balls: vec![],
+            threshold,
+        }
+    }
+
+    /// Add a new blob.
+    pub fn push(&mut self, center: Vec3, radius: f64, weight: f64) {
+        self.balls.push(Metaball {
+            center,
+            radius,
+            weight,
+        });
+    }
+
+    /// Consume the `Metaballs` and return new ones with the given blob.
+    pub fn with_ball(mut self, center: Vec3, radius: f64, weight: f64) -> Self {
+        self.push(center, radius, weight);
+        self
+    }
+
+    /// Return all the blobs.
+    pub fn balls(&self) -> &[Metaball] {
+        &self.balls
+    }
+}
+
+impl ImplicitFunction for Metaballs {
+    fn value(&self, p: &Vec3) -> f64 {
+        let field = self
+            .balls
+            .iter()
+            .map(|b| {
+                let x2 = p.dist2(b.center) / b.radius.powi(2);
+                if x2 >= 1.0 {
+                    0.0
+                } else {
+                    b.weight * (1.0 - x2).powi(3)
+                }
+            })
+            .sum::<f64>();
+
+        self.threshold - field
+    }
+
+    fn gradient(&self, p: &Vec3) -> Vec3 {
+        self.balls
+            .iter()
+            .map(|b| {
+                let d = *p - b.center;
+                let x2 = d.norm2() / b.radius.powi(2);
+                if x2 >= 1.0 {
+                    Vec3::zero()
+                } else {
+                    d * (6.0 * b.weight * (1.0 - x2).powi(2) / b.radius.powi(2))
+                }
+            })
+            .sum()
+    }
+
+    fn bbox(&self) -> Aabb {
+        // only the blobs with positive weights can make the field reach the
+        // threshold
+        let mut bboxes = self
+            .balls
+            .iter()
+            .filter(|b| b.weight > 0.0)
+            .map(|b| sphere::bounding_box(b.center, b.radius));
+
+        match bboxes.next() {
+            Some(first) => bboxes.fold(first, |acc, b| acc.union(&b)),
+            None => Aabb::new(Vec3::zero()),
+        }
+    }
+
+    fn lipschitz(&self) -> f64 {
+        // the derivative of w (1 - x^2)^3 wrt the distance reaches its maximum
+        // of 6 w x (1 - x^2)^2 / r at x = 1 / sqrt(5)
+        let x = 1.0 / 5.0_f64.sqrt();
+        let peak = 6.0 * x * (1.0 - x.powi(2)).powi(2);
+
+        self.balls
+            .iter()
+            .map(|b| peak * b.weight.abs() / b.radius)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metaballs() {
+        let geom = ImplicitGeometry::new(Metaballs::new(0.125).with_ball(Vec3::zero(), 2.0, 1.0));
+
+        // (1 - x^2)^3 = 1/8 when x^2 = 1/2
+        let radius = 2.0 / 2.0_f64.sqrt();
+
+        let r = Ray::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::new(2.0, 0.0, 0.0));
+        let t = geom.intersection(&r).unwrap().t;
+        assert!((t - (5.0 - radius) / 2.0).abs() < 1e-9);
+        assert!(
+            geom.normal_at(r.point_at(t))
+                .dist(Vec3::new(-1.0, 0.0, 0.0))
+                < 1e-6
+        );
+
+        let r = Ray::new(
+            Vec3::new(-5.0, radius + 0.01, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+        );
+        assert!(geom.intersection(&r).is_none());
+
+        // two close blobs merge together so the ray between them hits
+        let geom = ImplicitGeometry::new(
+            Metaballs::new(0.5)
+                .with_ball(Vec3::new(-0.6, 0.0, 0.0), 1.0, 1.0)
+                .with_ball(Vec3::new(0.6, 0.0, 0.0), 1.0, 1.0),
+        );
+        let r = Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(geom.intersection(&r).is_some());
+    }
+}
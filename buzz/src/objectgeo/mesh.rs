@@ -0,0 +1,178 @@
+use geo::{ray::Ray, spatial_index::Bvh, spatial_index::Shape, Aabb, Triangle, Vec3};
+
+use crate::{Boundary, FacetGeometry, Hit, Solid, Span, Surface};
+
+/// A triangle mesh stored in its own `Bvh` so that it's built only once.
+///
+/// To place many copies of the same mesh in a `Scene` wrap it in an `Arc` and
+/// create an instance for each copy with a `TransformedGeometry` inside a
+/// `SimpleObject`. All the instances share the same triangles and the `Bvh` of
+/// the `Scene` only stores their bounding boxes.
+#[derive(Debug, PartialEq, Clone)]
+pub struct MeshGeometry {
+    facets: Bvh<FacetGeometry>,
+}
+
+impl MeshGeometry {
+    pub fn new(triangles: impl IntoIterator<Item = Triangle>, flat_shading: bool) -> Self {
+        let facets = triangles
+            .into_iter()
+            .map(|t| FacetGeometry::new(t, flat_shading))
+            .collect();
+
+        MeshGeometry { facets }
+    }
+}
+
+impl Shape for MeshGeometry {
+    type Intersection = Hit;
+
+    fn intersection(&self, ray: &Ray) -> Option<Self::Intersection> {
+        let (facet, hit) = self
+            .facets
+            .intersections(ray)
+            .min_by(|(_, h0), (_, h1)| h0.t.partial_cmp(&h1.t).unwrap())?;
+
+        // the normal must be calculated here because it's not possible to know
+        // which facet was hit from the point alone
+        let p = ray.point_at(hit.t);
+        Some(Hit::new(hit.t, Some((p, facet.normal_at(p)))))
+    }
+
+    fn bbox(&self) -> Aabb {
+        self.facets
+            .bbox()
+            .unwrap_or_else(|| Aabb::new(Vec3::zero()))
+    }
+}
+
+impl Surface for MeshGeometry {
+    fn normal_at(&self, _p: Vec3) -> Vec3 {
+        unreachable!()
+    }
+}
+
+/// As a `Solid` the mesh must be closed and its facets must face outwards.
+impl Solid for MeshGeometry {
+    fn spans(&self, ray: &Ray) -> Vec<Span> {
+        let (tmin, _) = match self.bbox().ray_intersection(ray) {
+            Some(ts) => ts,
+            None => return vec![],
+        };
+
+        // start the ray before the mesh so that the hits behind the origin are
+        // found too
+        let start = tmin - 1.0;
+        let shifted = Ray {
+            origin: ray.point_at(start),
+            ..ray.clone()
+        };
+
+        let mut hits = self
+            .facets
+            .intersections(&shifted)
+            .map(|(facet, hit)| {
+                let t = start + hit.t;
+                Boundary::new(t, facet.normal_at(ray.point_at(t)))
+            })
+            .collect::<Vec<_>>();
+        hits.sort_by(|b0, b1| b0.t.total_cmp(&b1.t));
+
+        // a ray going through an edge hits all the facets that share it, hence
+        // decide whether it's entering or exiting from the facing of the facet
+        // rather than alternating
+        let mut enter = None;
+        let mut spans = vec![];
+        for b in hits {
+            let entering = b.normal.dot(ray.dir) < 0.0;
+
+            match enter {
+                None if entering => enter = Some(b),
+                Some(e) if !entering => {
+                    enter = None;
+                    spans.push(Span::new(e, b));
+                }
+                _ => {}
+            }
+        }
+
+        spans
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use geo::mat4::Mat4;
+
+    use super::*;
+    use crate::{Solid, TransformedGeometry};
+
+    #[test]
+    fn test_instances() {
+        let square = Arc::new(MeshGeometry::new(
+            vec![
+                Triangle::new(
+                    Vec3::zero(),
+                    Vec3::new(1.0, 0.0, 0.0),
+                    Vec3::new(1.0, 1.0, 0.0),
+                ),
+                Triangle::new(
+                    Vec3::zero(),
+                    Vec3::new(1.0, 1.0, 0.0),
+                    Vec3::new(0.0, 1.0, 0.0),
+                ),
+            ],
+            true,
+        ));
+
+        let ray = Ray::new(Vec3::new(0.25, 0.75, 5.0), Vec3::new(0.0, 0.0, -2.0));
+
+        let hit = square.intersection(&ray).unwrap();
+        assert_eq!(hit.t, 2.5);
+        assert_eq!(
+            hit.point_and_normal,
+            Some((Vec3::new(0.25, 0.75, 0.0), Vec3::new(0.0, 0.0, 1.0)))
+        );
+
+        let instance = TransformedGeometry::new(
+            Arc::clone(&square),
+            Mat4::translate(Vec3::new(0.0, 0.0, 1.0)),
+        );
+        let hit = instance.intersection(&ray).unwrap();
+        assert_eq!(hit.t, 2.0);
+        assert_eq!(
+            hit.point_and_normal,
+            Some((Vec3::new(0.25, 0.75, 1.0), Vec3::new(0.0, 0.0, 1.0)))
+        );
+
+        let ray = Ray::new(Vec3::new(1.5, 0.5, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(instance.intersection(&ray).is_none());
+    }
+
+    #[test]
+    fn test_spans() {
+        let cube = geo::mesh::load_mesh(
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("..")
+                .join("data")
+                .join("cube.stl"),
+        )
+        .unwrap();
+        let cube = MeshGeometry::new(cube.triangles(), true);
+        assert_eq!(cube.bbox(), Aabb::cube(Vec3::zero(), 2.0));
+
+        // the ray starts inside and goes through the diagonals of the faces
+        // shared by two triangles
+        let ray = Ray::new(Vec3::zero(), Vec3::new(0.0, 0.0, 2.0));
+        let spans = cube.spans(&ray);
+        assert_eq!(spans.len(), 1);
+        assert_eq!((spans[0].enter.t, spans[0].exit.t), (-0.5, 0.5));
+        assert_eq!(spans[0].enter.normal, Vec3::new(0.0, 0.0, -1.0));
+        assert_eq!(spans[0].exit.normal, Vec3::new(0.0, 0.0, 1.0));
+
+        let ray = Ray::new(Vec3::new(5.0, 0.0, 0.0), Vec3::new(-1.0, 0.0, -1.0));
+        assert!(cube.spans(&ray).is_empty());
+    }
+}
@@ -1,15 +1,35 @@
+pub mod animated;
+pub mod cone;
 pub mod csg;
 pub mod cube;
+pub mod curves;
 pub mod cylinder;
+pub mod disk;
 pub mod facet;
+pub mod heightfield;
+pub mod implicit;
+pub mod mesh;
 pub mod plane;
+pub mod quad;
+pub mod solid;
 pub mod sphere;
+pub mod torus;
 pub mod transformed;
 
+pub use animated::AnimatedGeometry;
+pub use cone::ConeGeometry;
 pub use csg::SdfGeometry;
 pub use cube::CubeGeometry;
-pub use cylinder::CylinderGeometry;
+pub use curves::{Curve, CurveKind, CurvesGeometry};
+pub use cylinder::{CappedCylinderGeometry, CylinderGeometry};
+pub use disk::DiskGeometry;
 pub use facet::FacetGeometry;
+pub use heightfield::HeightfieldGeometry;
+pub use implicit::ImplicitGeometry;
+pub use mesh::MeshGeometry;
 pub use plane::PlaneGeometry;
+pub use quad::QuadGeometry;
+pub use solid::{BooleanGeometry, BooleanOperation, Boundary, Solid, Span};
 pub use sphere::SphereGeometry;
+pub use torus::TorusGeometry;
 pub use transformed::TransformedGeometry;
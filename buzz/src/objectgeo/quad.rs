@@ -0,0 +1,112 @@
+use geo::{plane, ray::Ray, spatial_index::Shape, Aabb, Vec3};
+
+use crate::{sampler::Sampler, Hit, Surface};
+
+/// A finite parallelogram with a `corner` and two edges `u` and `v` starting
+/// from it. The normal is `u x v`, therefore the order of the edges determines
+/// which side the quad faces.
+#[derive(Debug, PartialEq, Clone)]
+pub struct QuadGeometry {
+    corner: Vec3,
+    u: Vec3,
+    v: Vec3,
+    normal: Vec3,
+
+    // used to project points on the plane onto the edges, see `uv_at`
+    w: Vec3,
+}
+
+impl QuadGeometry {
+    pub fn new(corner: Vec3, u: Vec3, v: Vec3) -> Self {
+        let n = u.cross(v);
+
+        QuadGeometry {
+            corner,
+            u,
+            v,
+            normal: n.normalized(),
+            w: n / n.norm2(),
+        }
+    }
+
+    /// Return the coordinates of the given point on the plane of the quad wrt
+    /// its edges. Points inside the quad have both coordinates in [0, 1] where
+    /// `(0, 0)` is the corner.
+    pub fn uv_at(&self, p: Vec3) -> (f64, f64) {
+        let d = p - self.corner;
+
+        (self.w.dot(d.cross(self.v)), self.w.dot(self.u.cross(d)))
+    }
+}
+
+impl Shape for QuadGeometry {
+    type Intersection = Hit;
+
+    fn bbox(&self) -> Aabb {
+        Aabb::new(self.corner)
+            .expanded(self.corner + self.u)
+            .expanded(self.corner + self.v)
+            .expanded(self.corner + self.u + self.v)
+    }
+
+    fn intersection(&self, ray: &Ray) -> Option<Self::Intersection> {
+        let t = plane::intersection(self.corner, self.normal, ray)?;
+
+        let (a, b) = self.uv_at(ray.point_at(t));
+        if !(0.0..=1.0).contains(&a) || !(0.0..=1.0).contains(&b) {
+            return None;
+        }
+
+        Some(Hit::new(t, None))
+    }
+}
+
+impl Surface for QuadGeometry {
+    fn normal_at(&self, _pt: Vec3) -> Vec3 {
+        self.normal
+    }
+
+    fn area(&self) -> Option<f64> {
+        Some(self.u.cross(self.v).norm())
+    }
+
+    fn sample(&self, sampler: &mut dyn Sampler) -> Option<Vec3> {
+        let (a, b) = sampler.next_2d();
+
+        Some(self.corner + self.u * a + self.v * b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intersection() {
+        let quad = QuadGeometry::new(
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(1.0, 4.0, 0.0),
+        );
+
+        assert_eq!(quad.area(), Some(8.0));
+        assert_eq!(quad.normal_at(Vec3::zero()), Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(quad.uv_at(Vec3::new(2.5, 3.0, 0.0)), (0.5, 0.5));
+
+        let hit = quad
+            .intersection(&Ray::new(
+                Vec3::new(2.5, 3.0, 2.0),
+                Vec3::new(0.0, 0.0, -1.0),
+            ))
+            .unwrap();
+        assert_eq!(hit.t, 2.0);
+
+        // the quad is slanted, hence (1.5, 4) is outside of it
+        assert!(quad
+            .intersection(&Ray::new(
+                Vec3::new(1.5, 4.0, 2.0),
+                Vec3::new(0.0, 0.0, -1.0),
+            ))
+            .is_none());
+    }
+}
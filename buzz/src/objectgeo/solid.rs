@@ -0,0 +1,316 @@
+//! Exact [constructive solid geometry][0] of analytic geometries.
+//!
+//! Unlike the shapes of the `csg` module that are described by signed distance
+//! functions and must be ray marched, a `Solid` computes all the spans where
+//! a ray is inside of it. The spans of two solids are then combined with
+//! boolean set operations which gives exact hits and normals, no matter how
+//! thin the features are.
+//!
+//! [0]: https://en.wikipedia.org/wiki/Constructive_solid_geometry
+
+use geo::Aabb;
+
+use crate::{Hit, Ray, Shape, Surface, Vec3};
+
+/// A closed geometry whose interior is well defined.
+pub trait Solid: Shape<Intersection = Hit> + Surface {
+    /// Return all the spans where the line of the ray is inside the solid,
+    /// sorted by t and disjoint. Since the whole line is considered the t
+    /// parameters can be negative, for example when the ray starts inside.
+    fn spans(&self, ray: &Ray) -> Vec<Span>;
+
+    /// Combine this solid with `other` keeping the points inside either of
+    /// them.
+    fn union<B: Solid>(self, other: B) -> BooleanGeometry<Self, B>
+    where
+        Self: Sized,
+    {
+        BooleanGeometry::new(BooleanOperation::Union, self, other)
+    }
+
+    /// Combine this solid with `other` keeping only the points inside both of
+    /// them.
+    fn intersect<B: Solid>(self, other: B) -> BooleanGeometry<Self, B>
+    where
+        Self: Sized,
+    {
+        BooleanGeometry::new(BooleanOperation::Intersection, self, other)
+    }
+
+    /// Remove `other` from this solid.
+    fn subtract<B: Solid>(self, other: B) -> BooleanGeometry<Self, B>
+    where
+        Self: Sized,
+    {
+        BooleanGeometry::new(BooleanOperation::Difference, self, other)
+    }
+}
+
+/// A point where a ray crosses the surface of a `Solid`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Boundary {
+    pub t: f64,
+
+    /// The normal of the surface pointing outside of the solid.
+    pub normal: Vec3,
+}
+
+/// The part of a ray between where it enters a `Solid` and where it exits it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Span {
+    pub enter: Boundary,
+    pub exit: Boundary,
+}
+
+impl Boundary {
+    pub fn new(t: f64, normal: Vec3) -> Self {
+        Boundary { t, normal }
+    }
+}
+
+impl Span {
+    pub fn new(enter: Boundary, exit: Boundary) -> Self {
+        Span { enter, exit }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BooleanOperation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl BooleanOperation {
+    fn contains(self, in_left: bool, in_right: bool) -> bool {
+        match self {
+            BooleanOperation::Union => in_left || in_right,
+            BooleanOperation::Intersection => in_left && in_right,
+            BooleanOperation::Difference => in_left && !in_right,
+        }
+    }
+}
+
+/// A `Solid` obtained by combining two other solids with a
+/// `BooleanOperation`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BooleanGeometry<A, B> {
+    operation: BooleanOperation,
+    left: A,
+    right: B,
+    bbox: Aabb,
+}
+
+impl<A: Solid, B: Solid> BooleanGeometry<A, B> {
+    pub fn new(operation: BooleanOperation, left: A, right: B) -> Self {
+        let bbox = match operation {
+            BooleanOperation::Union => left.bbox().union(&right.bbox()),
+            BooleanOperation::Intersection => left
+                .bbox()
+                .intersection(&right.bbox())
+                .unwrap_or_else(|| Aabb::new(left.bbox().center())),
+            BooleanOperation::Difference => left.bbox(),
+        };
+
+        BooleanGeometry {
+            operation,
+            left,
+            right,
+            bbox,
+        }
+    }
+}
+
+impl<A: Solid, B: Solid> Solid for BooleanGeometry<A, B> {
+    fn spans(&self, ray: &Ray) -> Vec<Span> {
+        if self.bbox.ray_intersection(ray).is_none() {
+            return vec![];
+        }
+
+        let left = self.left.spans(ray);
+        if left.is_empty() && self.operation != BooleanOperation::Union {
+            return left;
+        }
+
+        combine(self.operation, &left, &self.right.spans(ray))
+    }
+}
+
+impl<A: Solid, B: Solid> Shape for BooleanGeometry<A, B> {
+    type Intersection = Hit;
+
+    fn intersection(&self, ray: &Ray) -> Option<Self::Intersection> {
+        const EPS: f64 = 1e-6;
+
+        self.spans(ray)
+            .iter()
+            .flat_map(|s| [s.enter, s.exit])
+            .find(|b| b.t > EPS)
+            .map(|b| Hit::new(b.t, Some((ray.point_at(b.t), b.normal))))
+    }
+
+    fn bbox(&self) -> Aabb {
+        self.bbox.clone()
+    }
+}
+
+impl<A: Solid, B: Solid> Surface for BooleanGeometry<A, B> {
+    fn normal_at(&self, _p: Vec3) -> Vec3 {
+        unreachable!()
+    }
+}
+
+/// Combine the spans of two solids by sweeping all their boundaries in order
+/// and keeping track of whether the ray is inside each of them.
+fn combine(operation: BooleanOperation, left: &[Span], right: &[Span]) -> Vec<Span> {
+    let mut boundaries = Vec::with_capacity(2 * (left.len() + right.len()));
+    for (spans, is_left) in [(left, true), (right, false)] {
+        for s in spans {
+            boundaries.push((s.enter, is_left, true));
+            boundaries.push((s.exit, is_left, false));
+        }
+    }
+    boundaries.sort_by(|(b0, ..), (b1, ..)| b0.t.total_cmp(&b1.t));
+
+    let (mut in_left, mut in_right) = (false, false);
+    let mut enter = None;
+    let mut spans = vec![];
+
+    for (mut boundary, is_left, entering) in boundaries {
+        if is_left {
+            in_left = entering;
+        } else {
+            in_right = entering;
+        }
+
+        // the surface of the removed solid faces the other way
+        if !is_left && operation == BooleanOperation::Difference {
+            boundary.normal = -boundary.normal;
+        }
+
+        match (enter, operation.contains(in_left, in_right)) {
+            (None, true) => enter = Some(boundary),
+            (Some(e), false) => {
+                enter = None;
+                if boundary.t > e.t {
+                    spans.push(Span::new(e, boundary));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use geo::mat4::{Mat4, Transform};
+
+    use super::*;
+    use crate::{CubeGeometry, CylinderGeometry, SphereGeometry, TransformedGeometry};
+
+    #[test]
+    fn test_spans() {
+        let sphere = SphereGeometry::new(Vec3::zero(), 1.0);
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -2.0));
+        assert_eq!(
+            sphere.spans(&ray),
+            vec![Span::new(
+                Boundary::new(2.0, Vec3::new(0.0, 0.0, 1.0)),
+                Boundary::new(3.0, Vec3::new(0.0, 0.0, -1.0))
+            )]
+        );
+
+        let cube = CubeGeometry::new(Aabb::cube(Vec3::zero(), 1.0));
+        let ray = Ray::new(Vec3::zero(), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(
+            cube.spans(&ray),
+            vec![Span::new(
+                Boundary::new(-0.5, Vec3::new(-1.0, 0.0, 0.0)),
+                Boundary::new(0.5, Vec3::new(1.0, 0.0, 0.0))
+            )]
+        );
+
+        let cylinder = CylinderGeometry::new(0.5, (-1.0, 1.0));
+        let ray = Ray::new(Vec3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(
+            cylinder.spans(&ray),
+            vec![Span::new(
+                Boundary::new(4.0, Vec3::new(0.0, 0.0, -1.0)),
+                Boundary::new(6.0, Vec3::new(0.0, 0.0, 1.0))
+            )]
+        );
+        let ray = Ray::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(
+            cylinder.spans(&ray),
+            vec![Span::new(
+                Boundary::new(4.5, Vec3::new(-1.0, 0.0, 0.0)),
+                Boundary::new(5.5, Vec3::new(1.0, 0.0, 0.0))
+            )]
+        );
+        let ray = Ray::new(Vec3::new(-5.0, 0.0, 1.5), Vec3::new(1.0, 0.0, 0.0));
+        assert!(cylinder.spans(&ray).is_empty());
+
+        let moved = TransformedGeometry::new(
+            sphere,
+            Mat4::translate(Vec3::new(0.0, 0.0, 1.0)).transform(&Mat4::scale(Vec3::replicate(2.0))),
+        );
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -2.0));
+        let spans = moved.spans(&ray);
+        assert_eq!(spans.len(), 1);
+        assert!((spans[0].enter.t - 1.0).abs() < 1e-9);
+        assert!((spans[0].exit.t - 3.0).abs() < 1e-9);
+        assert!(spans[0].exit.normal.dist(Vec3::new(0.0, 0.0, -1.0)) < 1e-9);
+    }
+
+    #[test]
+    fn test_boolean_operations() {
+        let ray = Ray::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+
+        let a = || {
+            CubeGeometry::new(
+                Aabb::new(Vec3::new(-2.0, -1.0, -1.0)).expanded(Vec3::new(0.5, 1.0, 1.0)),
+            )
+        };
+        let b = || SphereGeometry::new(Vec3::zero(), 1.0);
+
+        let ts = |spans: Vec<Span>| {
+            spans
+                .iter()
+                .map(|s| (s.enter.t, s.exit.t))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(ts(a().union(b()).spans(&ray)), vec![(3.0, 6.0)]);
+        assert_eq!(ts(a().intersect(b()).spans(&ray)), vec![(4.0, 5.5)]);
+        assert_eq!(ts(a().subtract(b()).spans(&ray)), vec![(3.0, 4.0)]);
+        assert_eq!(ts(b().subtract(a()).spans(&ray)), vec![(5.5, 6.0)]);
+
+        // the hole is exact, even when it's very thin
+        let wall = CubeGeometry::new(Aabb::cube(Vec3::zero(), 2.0));
+        let hole = CylinderGeometry::new(1e-4, (-2.0, 2.0));
+        let geom = wall.subtract(hole);
+
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(geom.intersection(&ray).is_none());
+
+        let ray = Ray::new(Vec3::new(0.0, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        let hit = geom.intersection(&ray).unwrap();
+        assert!((hit.t - 4.0).abs() < 1e-9);
+
+        // the walls of the hole face the inside of the hole, both when starting
+        // inside the hole and inside the solid
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 0.5), Vec3::new(1.0, 0.0, 0.0));
+        let hit = geom.intersection(&ray).unwrap();
+        assert!((hit.t - 1e-4).abs() < 1e-9);
+        let (_, n) = hit.point_and_normal.unwrap();
+        assert!(n.dist(Vec3::new(-1.0, 0.0, 0.0)) < 1e-9);
+
+        let ray = Ray::new(Vec3::new(0.5, 0.0, 0.5), Vec3::new(-1.0, 0.0, 0.0));
+        let hit = geom.intersection(&ray).unwrap();
+        assert!((hit.t - (0.5 - 1e-4)).abs() < 1e-9);
+        let (_, n) = hit.point_and_normal.unwrap();
+        assert!(n.dist(Vec3::new(-1.0, 0.0, 0.0)) < 1e-9);
+    }
+}
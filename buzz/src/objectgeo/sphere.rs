@@ -1,6 +1,6 @@
 use geo::{ray::Ray, spatial_index::Shape, sphere, Aabb, Vec3};
 
-use crate::{Hit, Surface};
+use crate::{Boundary, Hit, Solid, Span, Surface};
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct SphereGeometry {
@@ -36,3 +36,24 @@ impl Surface for SphereGeometry {
         sphere::normal(self.center, pt)
     }
 }
+
+impl Solid for SphereGeometry {
+    fn spans(&self, ray: &Ray) -> Vec<Span> {
+        let oc = ray.origin - self.center;
+
+        let a = ray.dir.norm2();
+        let b = oc.dot(ray.dir);
+        let c = oc.norm2() - self.radius.powi(2);
+
+        let discr = b.powi(2) - a * c;
+        if discr <= 0.0 {
+            return vec![];
+        }
+
+        let boundary = |t| Boundary::new(t, self.normal_at(ray.point_at(t)));
+        vec![Span::new(
+            boundary((-b - discr.sqrt()) / a),
+            boundary((-b + discr.sqrt()) / a),
+        )]
+    }
+}
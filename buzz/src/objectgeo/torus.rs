@@ -0,0 +1,107 @@
+use geo::{ray::Ray, spatial_index::Shape, util::poly, Aabb, Vec3};
+
+use crate::{Hit, Surface};
+
+/// A torus centered at the origin lying on the XY plane. The `major_radius` is
+/// the distance from the origin to the center of the tube while the
+/// `minor_radius` is the radius of the tube. Use a `TransformedGeometry` to
+/// move it around.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TorusGeometry {
+    major_radius: f64,
+    minor_radius: f64,
+}
+
+impl TorusGeometry {
+    pub fn new(major_radius: f64, minor_radius: f64) -> Self {
+        TorusGeometry {
+            major_radius,
+            minor_radius,
+        }
+    }
+}
+
+impl Shape for TorusGeometry {
+    type Intersection = Hit;
+
+    fn intersection(&self, ray: &Ray) -> Option<Self::Intersection> {
+        // move the origin of the ray close to the torus to reduce the numerical
+        // error of the quartic solver
+        let (t0, _) = self.bbox().ray_intersection(ray)?;
+        let t0 = t0.max(0.0);
+        let o = ray.point_at(t0);
+        let d = ray.dir;
+
+        let r2 = self.major_radius.powi(2);
+
+        // (|p|^2 + R^2 - r^2)^2 = 4 R^2 (px^2 + py^2) where p = o + t d
+        let g = d.norm2();
+        let h = 2.0 * o.dot(d);
+        let i = o.norm2() + r2 - self.minor_radius.powi(2);
+        let j = d.x.powi(2) + d.y.powi(2);
+        let k = 2.0 * (o.x * d.x + o.y * d.y);
+        let m = o.x.powi(2) + o.y.powi(2);
+
+        let t = poly::solve_quartic(
+            g.powi(2),
+            2.0 * g * h,
+            h.powi(2) + 2.0 * g * i - 4.0 * r2 * j,
+            2.0 * h * i - 4.0 * r2 * k,
+            i.powi(2) - 4.0 * r2 * m,
+        )
+        .into_iter()
+        .map(|t| t + t0)
+        .find(|&t| t > 1e-6)?;
+
+        Some(Hit::new(t, None))
+    }
+
+    fn bbox(&self) -> Aabb {
+        let r = self.major_radius + self.minor_radius;
+
+        Aabb::new(Vec3::new(-r, -r, -self.minor_radius)).expanded(Vec3::new(
+            r,
+            r,
+            self.minor_radius,
+        ))
+    }
+}
+
+impl Surface for TorusGeometry {
+    fn normal_at(&self, p: Vec3) -> Vec3 {
+        // the normal points away from the closest point on the center circle
+        // of the tube
+        let ring = Vec3::new(p.x, p.y, 0.0).normalized() * self.major_radius;
+
+        (p - ring).normalized()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intersection() {
+        let torus = TorusGeometry::new(2.0, 0.5);
+
+        let r = Ray::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let t = torus.intersection(&r).unwrap().t;
+        assert!((t - 2.5).abs() < 1e-9);
+        assert!(
+            torus
+                .normal_at(r.point_at(t))
+                .dist(Vec3::new(-1.0, 0.0, 0.0))
+                < 1e-9
+        );
+
+        // the hole in the middle
+        let r = Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(torus.intersection(&r).is_none());
+
+        // from the inside of the tube
+        let r = Ray::new(Vec3::new(0.0, 2.0, 0.0), Vec3::new(0.0, 0.0, 2.0));
+        let t = torus.intersection(&r).unwrap().t;
+        assert!((t - 0.25).abs() < 1e-9);
+    }
+}
@@ -3,7 +3,7 @@ use geo::{
     Aabb,
 };
 
-use crate::{Hit, Ray, Shape, Surface, Vec3};
+use crate::{Boundary, Hit, Ray, Shape, Solid, Span, Surface, Vec3};
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct TransformedGeometry<S> {
@@ -34,13 +34,19 @@ where
         let transformed_ray = ray.transform(&self.inverse_trans);
         let hit = self.shape.intersection(&transformed_ray)?;
 
-        let p = transformed_ray.point_at(hit.t);
-        let n = self.shape.normal_at(p);
+        let (p, n) = hit.point_and_normal.unwrap_or_else(|| {
+            let p = transformed_ray.point_at(hit.t);
+            (p, self.shape.normal_at(p))
+        });
 
         let intersection = p.transform(&self.trans);
-        let tn = self.inverse_trans.transform_normal(&n);
+        let tn = self.inverse_trans.transpose().transform_normal(&n);
 
-        Some(Hit::new((p - ray.origin).norm(), Some((intersection, tn))))
+        Some(Hit {
+            t: intersection.dist(ray.origin) / ray.dir.norm(),
+            point_and_normal: Some((intersection, tn)),
+            ..hit
+        })
     }
 
     fn bbox(&self) -> Aabb {
@@ -56,3 +62,29 @@ where
         unreachable!()
     }
 }
+
+impl<S> Solid for TransformedGeometry<S>
+where
+    S: Solid,
+{
+    fn spans(&self, ray: &Ray) -> Vec<Span> {
+        let transformed_ray = ray.transform(&self.inverse_trans);
+        let normal_trans = self.inverse_trans.transpose();
+
+        // the direction of the transformed ray is normalized, hence the t
+        // parameters must be recomputed from the points
+        let transform = |b: Boundary| {
+            let p = transformed_ray.point_at(b.t).transform(&self.trans);
+            Boundary::new(
+                (p - ray.origin).dot(ray.dir) / ray.dir.norm2(),
+                normal_trans.transform_normal(&b.normal),
+            )
+        };
+
+        self.shape
+            .spans(&transformed_ray)
+            .into_iter()
+            .map(|s| Span::new(transform(s.enter), transform(s.exit)))
+            .collect()
+    }
+}
@@ -0,0 +1,129 @@
+//! Query what is visible at given pixels of an image rendered from a `Camera`,
+//! useful for interactive tools that need to select objects.
+
+use rayon::prelude::*;
+
+use geo::Vec3;
+
+use crate::{Camera, Material, RayKind, Scene};
+
+/// Information about the closest object visible through a pixel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pick {
+    /// the id of the `Surface` that was hit.
+    pub surface_id: usize,
+
+    /// the index of the primitive of the `Surface` that was hit, see
+    /// `Hit::primitive`.
+    pub primitive: usize,
+
+    /// the `Material` of the object.
+    pub material: Material,
+
+    /// the point in world space where the primary ray hit the object.
+    pub position: Vec3,
+
+    /// the unit normal of the object at `position`.
+    pub normal: Vec3,
+
+    /// the distance between the origin of the primary ray and `position`.
+    pub distance: f64,
+}
+
+/// Find what is visible through the center of the pixel `(x, y)` of an image
+/// of the given dimensions. Return `None` if the primary ray doesn't hit any
+/// object visible to the camera.
+pub fn pick(camera: &Camera, scene: &Scene, (x, y): (u32, u32), dims: (u32, u32)) -> Option<Pick> {
+    let ray = camera.cast_center_ray((x, y), dims);
+
+    let (object, hit) = scene.visible_intersection(&ray, RayKind::Camera)?;
+
+    let (position, normal) = hit.point_and_normal.unwrap_or_else(|| {
+        let p = ray.point_at(hit.t);
+        (p, object.normal_at(p))
+    });
+
+    Some(Pick {
+        surface_id: object.surface_id(),
+        primitive: hit.primitive,
+        material: hit.material.unwrap_or_else(|| object.material().clone()),
+        position,
+        normal: normal.normalized(),
+        distance: position.dist(ray.origin),
+    })
+}
+
+/// Find what is visible through all the pixels in the rectangle that goes from
+/// `(x0, y0)` included to `(x1, y1)` excluded. The picks are returned in row
+/// major order, that is the pick of `(x, y)` is at index
+/// `(y - y0) * (x1 - x0) + (x - x0)`. The rectangle is clamped to the image
+/// dimensions.
+pub fn pick_rect(
+    camera: &Camera,
+    scene: &Scene,
+    (x0, y0): (u32, u32),
+    (x1, y1): (u32, u32),
+    (width, height): (u32, u32),
+) -> Vec<Option<Pick>> {
+    let (x1, y1) = (x1.min(width), y1.min(height));
+    if x0 >= x1 || y0 >= y1 {
+        return vec![];
+    }
+
+    (y0..y1)
+        .into_par_iter()
+        .flat_map_iter(|y| (x0..x1).map(move |x| pick(camera, scene, (x, y), (width, height))))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{Environment, SceneObjects, SimpleObject, SphereGeometry};
+
+    #[test]
+    fn test_pick() {
+        let mut objects = SceneObjects::new();
+        objects.push(SimpleObject::new(
+            SphereGeometry::new(Vec3::new(10.0, 0.0, 0.0), 1.0),
+            Material::lambertian(Vec3::new(1.0, 0.0, 0.0)),
+        ));
+        objects.push(SimpleObject::new(
+            SphereGeometry::new(Vec3::new(10.0, 0.0, 5.0), 1.0),
+            Material::light(Vec3::new(1.0, 1.0, 1.0)),
+        ));
+        let scene = Scene::new(objects, Environment::Color(Vec3::zero()));
+
+        let camera = Camera::look_at(
+            Vec3::zero(),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            90.0,
+        );
+        let dims = (101, 101);
+
+        let p = pick(&camera, &scene, (50, 50), dims).unwrap();
+        assert_eq!(p.surface_id, 0);
+        assert_eq!(p.material, Material::lambertian(Vec3::new(1.0, 0.0, 0.0)));
+
+        let center = Vec3::new(10.0, 0.0, 0.0);
+        assert!((p.position.dist(center) - 1.0).abs() < 1e-6);
+        assert!(p.normal.dist(p.position - center) < 1e-6);
+        assert!(p.normal.x < -0.9);
+        assert!((p.distance - p.position.norm()).abs() < 1e-9);
+
+        // the light is above the sphere, that is towards the top of the image
+        let p = pick(&camera, &scene, (50, 25), dims).unwrap();
+        assert_eq!(p.surface_id, 1);
+
+        assert_eq!(pick(&camera, &scene, (0, 0), dims), None);
+
+        let picks = pick_rect(&camera, &scene, (48, 48), (53, 200), dims);
+        assert_eq!(picks.len(), 5 * 53);
+        assert_eq!(picks[2 * 5 + 2], pick(&camera, &scene, (50, 50), dims));
+        assert!(picks.iter().flatten().all(|p| p.surface_id == 0));
+
+        assert!(pick_rect(&camera, &scene, (5, 5), (5, 10), dims).is_empty());
+    }
+}
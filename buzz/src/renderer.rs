@@ -1,6 +1,6 @@
 use geo::{ray::Ray, spatial_index::Intersection, Vec3};
 
-use std::convert::TryFrom;
+use std::{convert::TryFrom, f64::consts::PI, ops::Range};
 
 use image::{Rgb, RgbImage};
 use rand::prelude::*;
@@ -9,7 +9,8 @@ use rayon::prelude::*;
 
 use crate::{
     material::{dielectric_bounce, lambertian_bounce, metal_bounce, Material},
-    Camera, Environment, Object, Scene,
+    sampler::{HaltonSampler, Sampler, Sampling, SobolSampler, StratifiedSampler},
+    Camera, Environment, Filter, Hit, Object, RayKind, Scene,
 };
 
 /// Simple struct to hold rendering params together.
@@ -37,71 +38,252 @@ pub struct RenderConfig {
     /// width and height of the rendered image.
     pub width: u32,
     pub height: u32,
+
+    /// the filter used to reconstruct each pixel from the samples around it.
+    pub filter: Filter,
+
+    /// the strategy used to generate the random numbers of each sample.
+    pub sampling: Sampling,
 }
 
 /// Render a `Scene` from a `Camera` to a new `RgbImage` of the given
 /// dimensions.
 pub fn render(camera: &Camera, scene: &Scene, config: &RenderConfig) -> image::RgbImage {
-    let lights = if config.direct_lighting {
-        scene.lights().collect::<Vec<_>>()
-    } else {
-        vec![]
-    };
-
-    let mut rng = XorShiftRng::seed_from_u64(thread_rng().gen());
-    let mut img = RgbImage::new(config.width, config.height);
+    let mut film = Film::new(0, config.height, config.width);
+    film.merge(&render_band(0..config.height, camera, scene, config));
 
-    for (x, y, pix) in img.enumerate_pixels_mut() {
-        *pix = render_pixel((x, y), camera, scene, &lights, &mut rng, config);
-    }
-
-    img
+    film.to_image()
 }
 
 /// Render a `Scene` from a `Camera` to a new `RgbImage` of the given dimensions
 /// concurrently.
 pub fn parallel_render(camera: &Camera, scene: &Scene, config: &RenderConfig) -> image::RgbImage {
-    let lights = if config.direct_lighting {
-        scene.lights().collect::<Vec<_>>()
-    } else {
-        vec![]
-    };
-
-    let mut img = RgbImage::new(config.width, config.height);
-
-    img.par_chunks_mut(3 * usize::try_from(config.width).unwrap())
-        .zip((0_u32..config.height).into_par_iter())
-        .for_each(|(row, y)| {
-            let mut rng = XorShiftRng::seed_from_u64(thread_rng().gen());
-
-            for (pix, x) in row.chunks_mut(3).zip(0..) {
-                let Rgb([r, g, b]) = render_pixel((x, y), camera, scene, &lights, &mut rng, config);
+    // the image is split in bands of rows that are rendered in parallel. Since
+    // the samples near the borders of a band contribute to the pixels of the
+    // neighbor bands too, each band also covers some extra rows that are then
+    // merged together.
+    let bands = (0..config.height)
+        .step_by(usize::try_from(BAND_HEIGHT).unwrap())
+        .collect::<Vec<_>>()
+        .into_par_iter()
+        .map(|y| {
+            let rows = y..(y + BAND_HEIGHT).min(config.height);
+
+            render_band(rows, camera, scene, config)
+        })
+        .collect::<Vec<_>>();
 
-                pix[0] = r;
-                pix[1] = g;
-                pix[2] = b;
-            }
-        });
+    let mut film = Film::new(0, config.height, config.width);
+    for band in &bands {
+        film.merge(band);
+    }
 
-    img
+    film.to_image()
 }
 
-/// Render a single pixel of an image from a `Scene` and `Camera`.
+/// Render a single pixel of an image from a `Scene` and `Camera`. The samples
+/// are spread over the area covered by the filter in the config and weighted
+/// like `render` does, so that the pixel matches the rendered image.
 pub fn render_pixel(
     (x, y): (u32, u32),
     camera: &Camera,
     scene: &Scene,
-    lights: &[&dyn Object],
-    rng: &mut impl Rng,
+    sampler: &mut impl Sampler,
     config: &RenderConfig,
 ) -> Rgb<u8> {
-    let mut c = (0..config.samples)
-        .map(|_| {
-            let r = camera.cast_ray((x, y), (config.width, config.height), rng);
-            sample(scene, lights, &r, 0, rng, config)
-        })
-        .sum::<Vec3>()
-        / f64::from(config.samples);
+    let radius = config.filter.radius();
+
+    let mut sum = Vec3::zero();
+    let mut weights = 0.0;
+    for i in 0..config.samples {
+        sampler.start_sample((x, y), i);
+
+        let (u, v) = sampler.next_2d();
+        let (dx, dy) = ((u * 2.0 - 1.0) * radius, (v * 2.0 - 1.0) * radius);
+        let (fx, fy) = (f64::from(x) + 0.5 + dx, f64::from(y) + 0.5 + dy);
+
+        let w = config.filter.weight(dx, dy);
+        if w == 0.0 {
+            continue;
+        }
+
+        let r = camera.cast_ray_at((fx, fy), (config.width, config.height), sampler);
+        sum += sample(scene, &r, 0, sampler, config) * w;
+        weights += w;
+    }
+
+    if weights > 0.0 {
+        to_rgb(sum / weights)
+    } else {
+        Rgb([0, 0, 0])
+    }
+}
+
+/// Number of rows in each band rendered by `parallel_render`.
+const BAND_HEIGHT: u32 = 16;
+
+/// A `Film` collects the weighted sum of the samples that contribute to each
+/// pixel in a range of rows of the image.
+#[derive(Debug)]
+struct Film {
+    y0: i64,
+    height: u32,
+    width: u32,
+    pixels: Vec<(Vec3, f64)>,
+}
+
+/// Render the given rows with a new `Sampler` of the kind specified in the
+/// config.
+fn render_band(rows: Range<u32>, camera: &Camera, scene: &Scene, config: &RenderConfig) -> Film {
+    let seed = thread_rng().gen();
+
+    match config.sampling {
+        Sampling::Random => {
+            let mut rng = XorShiftRng::seed_from_u64(seed);
+            render_rows(rows, camera, scene, &mut rng, config)
+        }
+        Sampling::Stratified => {
+            let mut sampler = StratifiedSampler::new(config.samples, seed);
+            render_rows(rows, camera, scene, &mut sampler, config)
+        }
+        Sampling::Halton => {
+            let mut sampler = HaltonSampler::new(seed);
+            render_rows(rows, camera, scene, &mut sampler, config)
+        }
+        Sampling::Sobol => {
+            let mut sampler = SobolSampler::new(seed);
+            render_rows(rows, camera, scene, &mut sampler, config)
+        }
+    }
+}
+
+/// Render all the samples of the pixels in the given rows. The returned `Film`
+/// also includes the rows around `rows` that the samples contribute to.
+fn render_rows(
+    rows: Range<u32>,
+    camera: &Camera,
+    scene: &Scene,
+    sampler: &mut impl Sampler,
+    config: &RenderConfig,
+) -> Film {
+    let mut film = Film::for_rows(&rows, config.width, &config.filter);
+
+    for y in rows {
+        for x in 0..config.width {
+            for i in 0..config.samples {
+                sampler.start_sample((x, y), i);
+
+                let (u, v) = sampler.next_2d();
+                let (fx, fy) = (f64::from(x) + u, f64::from(y) + v);
+
+                let r = camera.cast_ray_at((fx, fy), (config.width, config.height), sampler);
+                let c = sample(scene, &r, 0, sampler, config);
+
+                film.add_sample((fx, fy), c, &config.filter);
+            }
+        }
+    }
+
+    film
+}
+
+impl Film {
+    fn new(y0: i64, height: u32, width: u32) -> Self {
+        Film {
+            y0,
+            height,
+            width,
+            pixels: vec![(Vec3::zero(), 0.0); width as usize * height as usize],
+        }
+    }
+
+    /// Create a `Film` for the given rows plus the rows around them that the
+    /// samples taken inside `rows` contribute to.
+    fn for_rows(rows: &Range<u32>, width: u32, filter: &Filter) -> Self {
+        let margin = (filter.radius() + 0.5).ceil() as u32;
+
+        Film::new(
+            i64::from(rows.start) - i64::from(margin),
+            rows.end - rows.start + 2 * margin,
+            width,
+        )
+    }
+
+    fn index(&self, x: i64, y: i64) -> Option<usize> {
+        let y = y - self.y0;
+
+        if x < 0 || y < 0 || x >= i64::from(self.width) || y >= i64::from(self.height) {
+            return None;
+        }
+
+        usize::try_from(y * i64::from(self.width) + x).ok()
+    }
+
+    /// Add a sample of color `c` taken at the continuous image position
+    /// `(fx, fy)` to all the pixels within the filter radius.
+    fn add_sample(&mut self, (fx, fy): (f64, f64), c: Vec3, filter: &Filter) {
+        let r = filter.radius();
+
+        let x0 = (fx - 0.5 - r).ceil() as i64;
+        let x1 = (fx - 0.5 + r).floor() as i64;
+        let y0 = (fy - 0.5 - r).ceil() as i64;
+        let y1 = (fy - 0.5 + r).floor() as i64;
+
+        for py in y0..=y1 {
+            for px in x0..=x1 {
+                let i = match self.index(px, py) {
+                    Some(i) => i,
+                    None => continue,
+                };
+
+                let w = filter.weight(fx - px as f64 - 0.5, fy - py as f64 - 0.5);
+                if w == 0.0 {
+                    continue;
+                }
+
+                let (sum, weights) = &mut self.pixels[i];
+                *sum += c * w;
+                *weights += w;
+            }
+        }
+    }
+
+    /// Add all the contributions of another `Film` to the overlapping rows.
+    fn merge(&mut self, other: &Film) {
+        for y in other.y0..other.y0 + i64::from(other.height) {
+            for x in 0..i64::from(self.width) {
+                let (i, j) = match (self.index(x, y), other.index(x, y)) {
+                    (Some(i), Some(j)) => (i, j),
+                    _ => continue,
+                };
+
+                let (sum, weights) = other.pixels[j];
+                self.pixels[i].0 += sum;
+                self.pixels[i].1 += weights;
+            }
+        }
+    }
+
+    fn to_image(&self) -> RgbImage {
+        let mut img = RgbImage::new(self.width, self.height);
+
+        for (pix, (sum, weights)) in img.pixels_mut().zip(&self.pixels) {
+            *pix = if *weights > 0.0 {
+                to_rgb(*sum / *weights)
+            } else {
+                Rgb([0, 0, 0])
+            };
+        }
+
+        img
+    }
+}
+
+fn to_rgb(mut c: Vec3) -> Rgb<u8> {
+    // filters with negative lobes might produce colors out of range
+    c.x = c.x.clamp(0.0, 1.0);
+    c.y = c.y.clamp(0.0, 1.0);
+    c.z = c.z.clamp(0.0, 1.0);
 
     // gamma correct pixels
     c.x = c.x.sqrt();
@@ -117,13 +299,18 @@ pub fn render_pixel(
 
 fn sample(
     scene: &Scene,
-    lights: &[&dyn Object],
     ray: &Ray,
     depth: u32,
-    rng: &mut impl Rng,
+    sampler: &mut impl Sampler,
     config: &RenderConfig,
 ) -> Vec3 {
-    match scene.intersection(ray) {
+    let kind = if depth == 0 {
+        RayKind::Camera
+    } else {
+        RayKind::Reflection
+    };
+
+    match scene.visible_intersection(ray, kind) {
         Some(_) if depth >= config.max_bounces => Vec3::zero(),
         Some((s, hit)) => {
             let (intersection, n) = hit.point_and_normal.unwrap_or_else(|| {
@@ -135,13 +322,13 @@ fn sample(
 
             sample_material(
                 scene,
-                lights,
                 &ray,
                 depth,
-                s.material(),
+                s,
                 intersection,
                 n,
-                rng,
+                &hit,
+                sampler,
                 config,
             )
         }
@@ -150,96 +337,122 @@ fn sample(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn sample_material(
     scene: &Scene,
-    lights: &[&dyn Object],
     ray: &Ray,
     depth: u32,
-    material: &Material,
+    object: &dyn Object,
     intersection: Vec3,
     n: Vec3,
-    rng: &mut impl Rng,
+    hit: &Hit,
+    sampler: &mut impl Sampler,
     config: &RenderConfig,
 ) -> Vec3 {
+    let material = hit.material.as_ref().unwrap_or_else(|| object.material());
+
     match *material {
-        Material::Lambertian { albedo } => {
+        Material::Lambertian { .. } | Material::OrbitTrap { .. } => {
+            let albedo = material.diffuse_albedo(hit.orbit_trap).unwrap();
+
             let indirect = sample(
                 scene,
-                lights,
-                &lambertian_bounce(intersection, n, rng),
+                &lambertian_bounce(intersection, n, sampler).with_time(ray.time),
                 depth + 1,
-                rng,
+                sampler,
                 config,
             );
 
-            let direct = lights
-                .iter()
-                .map(|l| sample_light(scene, *l, ray, intersection, n, config, rng))
-                .sum::<Vec3>();
+            let direct = if config.direct_lighting {
+                scene
+                    .lights_for(object)
+                    .map(|l| sample_light(scene, object, l, ray, intersection, n, config, sampler))
+                    .sum::<Vec3>()
+            } else {
+                Vec3::zero()
+            };
 
             albedo * (direct + indirect)
         }
         Material::Metal { albedo, fuzziness } => {
-            let r = metal_bounce(ray, intersection, n, fuzziness, rng);
+            let r = metal_bounce(ray, intersection, n, fuzziness, sampler).with_time(ray.time);
 
             if r.dir.dot(n) < 0.0 {
                 return Vec3::zero();
             }
 
-            albedo * sample(scene, lights, &r, depth + 1, rng, config)
+            albedo * sample(scene, &r, depth + 1, sampler, config)
         }
         Material::Dielectric { refraction_index } => sample(
             scene,
-            lights,
-            &dielectric_bounce(ray, intersection, n, refraction_index, rng),
+            &dielectric_bounce(ray, intersection, n, refraction_index, sampler).with_time(ray.time),
             depth + 1,
-            rng,
+            sampler,
             config,
         ),
         Material::Light { emittance } => emittance,
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn sample_light(
     scene: &Scene,
+    object: &dyn Object,
     light: &dyn Object,
     ray: &Ray,
     intersection: Vec3,
     n: Vec3,
     config: &RenderConfig,
-    rng: &mut impl Rng,
+    sampler: &mut impl Sampler,
 ) -> Vec3 {
     let (mut light_pos, light_radius) = light.bounding_sphere();
 
     if config.soft_shadows {
-        light_pos = loop {
-            let x = rng.gen::<f64>() * 2.0 - 1.0;
-            let y = rng.gen::<f64>() * 2.0 - 1.0;
+        match light.sample(sampler) {
+            Some(p) => light_pos = p,
+            None => {
+                // uniformly sample a disc facing the origin of the ray
+                let (a, b) = sampler.next_2d();
+                let (r, theta) = (a.sqrt() * light_radius, b * 2.0 * PI);
 
-            if x.powi(2) + y.powi(2) <= 1.0 {
                 let l = (light_pos - ray.origin).normalized();
-                let u = l.cross(Vec3::random_unit(rng)).normalized();
+                let helper = if l.x.abs() > 0.9 {
+                    Vec3::new(0.0, 1.0, 0.0)
+                } else {
+                    Vec3::new(1.0, 0.0, 0.0)
+                };
+                let u = l.cross(helper).normalized();
                 let v = l.cross(u);
 
-                break light_pos + (u * (x * light_radius)) + (v * (y * light_radius));
+                light_pos = light_pos + u * (r * theta.cos()) + v * (r * theta.sin());
             }
-        };
+        }
     }
 
-    let light_ray = Ray::new(intersection, (light_pos - intersection).normalized());
+    let light_ray =
+        Ray::new(intersection, (light_pos - intersection).normalized()).with_time(ray.time);
 
     // if `light_ray` goes in the opposite direction wrt `n` then it doesn't
     // reach the light for sure
-    let diffuse = light_ray.dir.dot(n);
+    let mut diffuse = light_ray.dir.dot(n);
     if diffuse <= 0.0 {
         return Vec3::zero();
     }
 
+    // lights with a known area are weighted by the solid angle they cover wrt
+    // the one of the hemisphere, the others as if they covered all of it
+    if let Some(area) = light.area() {
+        let cos = light.normal_at(light_pos).dot(light_ray.dir).abs();
+        diffuse *= area * cos / (PI * light_pos.dist2(intersection));
+    }
+
     // check if `intersection` is in the shadow of another object or reaches
     // a light
-    if let Some((o, _t)) = scene.intersection(&light_ray) {
-        if let Material::Light { emittance } = o.material() {
-            return *emittance * diffuse;
+    if let Some((o, hit)) = scene.shadow_intersection(&light_ray, light.surface_id()) {
+        if let Material::Light { emittance } = hit.material.as_ref().unwrap_or(o.material()) {
+            if object.is_lit_by(o.surface_id()) {
+                return *emittance * diffuse;
+            }
         }
     }
 
@@ -255,3 +468,238 @@ fn sample_environment(scene: &Scene, ray: &Ray) -> Vec3 {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use geo::mat4::Mat4;
+
+    use crate::{
+        csg::{self, SignedDistanceFunction},
+        CylinderGeometry, DiskGeometry, LightLinks, PlaneGeometry, SceneObjects, SdfGeometry,
+        SimpleObject, SphereGeometry,
+    };
+
+    fn config() -> RenderConfig {
+        RenderConfig {
+            width: 4,
+            height: 4,
+            max_bounces: 1,
+            samples: 4,
+            direct_lighting: true,
+            soft_shadows: false,
+            filter: Filter::default(),
+            sampling: Sampling::default(),
+        }
+    }
+
+    #[test]
+    fn test_film_merge() {
+        let (width, height) = (7, 20);
+
+        // deterministic samples spread unevenly over the image
+        let samples = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .flat_map(|(x, y)| {
+                (0..3).map(move |i| {
+                    let u = f64::from((x * 7 + y * 3 + i * 5) % 10) / 10.0;
+                    let v = f64::from((x * 3 + y * 7 + i * 3) % 10) / 10.0;
+                    let c = Vec3::new(f64::from(x) / 7.0, f64::from(y) / 20.0, u * v);
+
+                    (y, (f64::from(x) + u, f64::from(y) + v), c)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for filter in [
+            Filter::default(),
+            Filter::Tent { radius: 1.5 },
+            Filter::Gaussian {
+                radius: 2.0,
+                alpha: 2.0,
+            },
+            Filter::Lanczos {
+                radius: 3.0,
+                tau: 3.0,
+            },
+        ] {
+            let mut single = Film::new(0, height, width);
+            for (_, p, c) in &samples {
+                single.add_sample(*p, *c, &filter);
+            }
+
+            // bands of rows that don't divide the image evenly
+            let mut merged = Film::new(0, height, width);
+            for y in (0..height).step_by(6) {
+                let rows = y..(y + 6).min(height);
+
+                let mut band = Film::for_rows(&rows, width, &filter);
+                for (_, p, c) in samples.iter().filter(|(sy, ..)| rows.contains(sy)) {
+                    band.add_sample(*p, *c, &filter);
+                }
+                merged.merge(&band);
+            }
+
+            for ((s, sw), (m, mw)) in single.pixels.iter().zip(&merged.pixels) {
+                assert!((sw - mw).abs() < 1e-9, "{:?}", filter);
+                assert!(s.dist(*m) < 1e-9, "{:?}", filter);
+            }
+            assert_eq!(single.to_image(), merged.to_image());
+        }
+    }
+
+    #[test]
+    fn test_render_pixel_filter() {
+        // vertical light stripe that exactly covers the middle column of the
+        // image
+        let mut objects = SceneObjects::new();
+        objects.push(SimpleObject::new(
+            CylinderGeometry::new(0.5, (-10.0, 10.0)),
+            Material::light(Vec3::replicate(1.0)),
+        ));
+        let scene = Scene::new(objects, Environment::Color(Vec3::zero()));
+
+        let camera = Camera::orthographic(
+            Vec3::new(5.0, 0.0, 0.0),
+            Vec3::zero(),
+            Vec3::new(0.0, 0.0, 1.0),
+            2.0,
+        );
+
+        let render_center = |filter| {
+            let config = RenderConfig {
+                width: 3,
+                height: 3,
+                samples: 16,
+                filter,
+                ..config()
+            };
+            let mut rng = XorShiftRng::seed_from_u64(0);
+            render_pixel((1, 1), &camera, &scene, &mut rng, &config)
+        };
+
+        assert_eq!(
+            render_center(Filter::Box { radius: 0.5 }),
+            Rgb([255, 255, 255])
+        );
+
+        // a wider filter also picks up the dark columns around the stripe
+        let Rgb([r, g, b]) = render_center(Filter::Tent { radius: 1.5 });
+        assert!(r > 0 && r < 255);
+        assert_eq!((r, r), (g, b));
+    }
+
+    #[test]
+    fn test_light_links() {
+        let render_floor = |light_links| {
+            let mut objects = SceneObjects::new();
+            objects.push(SimpleObject::new(
+                SphereGeometry::new(Vec3::new(0.0, 0.0, 5.0), 0.5),
+                Material::light(Vec3::replicate(1.0)),
+            ));
+            objects.push(
+                SimpleObject::new(
+                    PlaneGeometry::new(Vec3::zero(), Vec3::new(0.0, 0.0, 1.0)),
+                    Material::lambertian(Vec3::replicate(1.0)),
+                )
+                .with_light_links(light_links),
+            );
+            let scene = Scene::new(objects, Environment::Color(Vec3::zero()));
+
+            let camera = Camera::look_at(
+                Vec3::new(-2.0, 0.0, 2.0),
+                Vec3::zero(),
+                Vec3::new(0.0, 0.0, 1.0),
+                30.0,
+            );
+            render(&camera, &scene, &config())
+        };
+
+        // with a single bounce the floor only receives direct light
+        let lit = render_floor(LightLinks::All);
+        assert!(lit.pixels().all(|p| p.0 != [0, 0, 0]));
+
+        let unlinked = render_floor(LightLinks::Except(vec![0]));
+        assert!(unlinked.pixels().all(|p| p.0 == [0, 0, 0]));
+    }
+
+    #[test]
+    fn test_shadow_hit_materials() {
+        let render_floor = |leaf_material| {
+            let sphere = csg::Sphere::new(0.5)
+                .transformed(Mat4::translate(Vec3::new(0.0, 0.0, 5.0)))
+                .with_material(leaf_material);
+
+            let mut objects = SceneObjects::new();
+            objects.push(SimpleObject::new(
+                SdfGeometry::new(sphere),
+                Material::light(Vec3::replicate(1.0)),
+            ));
+            objects.push(SimpleObject::new(
+                PlaneGeometry::new(Vec3::zero(), Vec3::new(0.0, 0.0, 1.0)),
+                Material::lambertian(Vec3::replicate(1.0)),
+            ));
+            let scene = Scene::new(objects, Environment::Color(Vec3::zero()));
+
+            let camera = Camera::look_at(
+                Vec3::new(-2.0, 0.0, 2.0),
+                Vec3::zero(),
+                Vec3::new(0.0, 0.0, 1.0),
+                30.0,
+            );
+            render(&camera, &scene, &config())
+        };
+
+        let lit = render_floor(Material::light(Vec3::replicate(1.0)));
+        assert!(lit.pixels().all(|p| p.0 != [0, 0, 0]));
+
+        // the light is sampled, but the material of its surface doesn't emit
+        let covered = render_floor(Material::lambertian(Vec3::replicate(1.0)));
+        assert!(covered.pixels().all(|p| p.0 == [0, 0, 0]));
+    }
+
+    #[test]
+    fn test_area_lights() {
+        let render_floor = |radius, soft_shadows| {
+            let mut objects = SceneObjects::new();
+            objects.push(SimpleObject::new(
+                DiskGeometry::new(Vec3::new(0.0, 0.0, 2.0), Vec3::new(0.0, 0.0, -1.0), radius),
+                Material::light(Vec3::replicate(1.0)),
+            ));
+            objects.push(SimpleObject::new(
+                PlaneGeometry::new(Vec3::zero(), Vec3::new(0.0, 0.0, 1.0)),
+                Material::lambertian(Vec3::replicate(1.0)),
+            ));
+            let scene = Scene::new(objects, Environment::Color(Vec3::zero()));
+
+            let camera = Camera::look_at(
+                Vec3::new(-1.5, 0.0, 1.5),
+                Vec3::zero(),
+                Vec3::new(0.0, 0.0, 1.0),
+                30.0,
+            );
+            let img = render(
+                &camera,
+                &scene,
+                &RenderConfig {
+                    soft_shadows,
+                    ..config()
+                },
+            );
+
+            img.pixels().map(|p| u32::from(p.0[0])).collect::<Vec<_>>()
+        };
+
+        // the light is weighted by the solid angle it covers, hence a bigger
+        // light is brighter
+        for soft_shadows in [false, true] {
+            let small = render_floor(0.5, soft_shadows);
+            let big = render_floor(1.0, soft_shadows);
+
+            assert!(small.iter().all(|&p| p > 0));
+            assert!(small.iter().zip(&big).all(|(s, b)| s < b));
+            assert!(small.iter().sum::<u32>() * 3 < big.iter().sum::<u32>() * 2);
+        }
+    }
+}
@@ -0,0 +1,480 @@
+//! Samplers generate the random numbers used to take decisions while
+//! rendering, that is where to cast camera rays, how to bounce off materials
+//! and which point of a light to sample.
+//!
+//! Low discrepancy samplers spread the samples of each pixel more evenly than
+//! plain random numbers, therefore the noise drops faster as the number of
+//! samples grows.
+
+use rand::{Rng, SeedableRng};
+use rand_xorshift::XorShiftRng;
+
+/// A `Sampler` provides a sequence of sample dimensions in [0, 1) for each
+/// sample of a pixel. Each call to `next_1d` or `next_2d` consumes one or two
+/// dimensions.
+pub trait Sampler {
+    /// Prepare the `Sampler` to generate the `index`-th sample of the given
+    /// pixel. This resets the current dimension.
+    fn start_sample(&mut self, _pixel: (u32, u32), _index: u32) {}
+
+    /// Return the next dimension of the current sample.
+    fn next_1d(&mut self) -> f64;
+
+    /// Return the next two dimensions of the current sample.
+    fn next_2d(&mut self) -> (f64, f64) {
+        let u = self.next_1d();
+        let v = self.next_1d();
+        (u, v)
+    }
+}
+
+/// Every `Rng` is a `Sampler` that returns independent random numbers.
+impl<R: Rng + ?Sized> Sampler for R {
+    fn next_1d(&mut self) -> f64 {
+        self.gen()
+    }
+}
+
+/// Enum over the samplers that the renderer can use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Sampling {
+    /// Independent random samples.
+    #[default]
+    Random,
+
+    /// Stratified jittered samples, see `StratifiedSampler`.
+    Stratified,
+
+    /// Randomized Halton sequence, see `HaltonSampler`.
+    Halton,
+
+    /// Scrambled Sobol sequence, see `SobolSampler`.
+    Sobol,
+}
+
+/// [Stratified sampler][0] that splits each dimension in as many strata as the
+/// number of samples per pixel and places each sample in a different stratum
+/// jittered randomly. The strata are shuffled for each pixel and dimension so
+/// that dimensions are not correlated.
+///
+/// [0]: https://www.pbr-book.org/3ed-2018/Sampling_and_Reconstruction/Stratified_Sampling
+#[derive(Debug, Clone)]
+pub struct StratifiedSampler {
+    samples_per_pixel: u32,
+    state: SampleState,
+    rng: XorShiftRng,
+}
+
+/// [Halton sequence][0] sampler where each dimension uses the radical inverse
+/// in a different prime base. The sequence is randomized for each pixel by a
+/// random shift. Dimensions beyond the supported ones fall back to random
+/// numbers.
+///
+/// [0]: https://en.wikipedia.org/wiki/Halton_sequence
+#[derive(Debug, Clone)]
+pub struct HaltonSampler {
+    state: SampleState,
+    rng: XorShiftRng,
+}
+
+/// [Sobol sequence][0] sampler scrambled for each pixel and dimension by a
+/// random digital shift. Dimensions beyond the supported ones fall back to
+/// random numbers.
+///
+/// [0]: https://en.wikipedia.org/wiki/Sobol_sequence
+#[derive(Debug, Clone)]
+pub struct SobolSampler {
+    state: SampleState,
+    rng: XorShiftRng,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SampleState {
+    seed: u64,
+    pixel: (u32, u32),
+    index: u32,
+    dimension: u32,
+}
+
+impl StratifiedSampler {
+    /// Create a `StratifiedSampler` that takes the given number of samples per
+    /// pixel.
+    pub fn new(samples_per_pixel: u32, seed: u64) -> Self {
+        StratifiedSampler {
+            samples_per_pixel: samples_per_pixel.max(1),
+            state: SampleState::new(seed),
+            rng: XorShiftRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Sampler for StratifiedSampler {
+    fn start_sample(&mut self, pixel: (u32, u32), index: u32) {
+        self.state.start(pixel, index);
+    }
+
+    fn next_1d(&mut self) -> f64 {
+        let n = self.samples_per_pixel;
+        let h = self.state.next_hash();
+
+        let stratum = permute(self.state.index % n, n, h as u32);
+        (f64::from(stratum) + self.rng.gen::<f64>()) / f64::from(n)
+    }
+
+    fn next_2d(&mut self) -> (f64, f64) {
+        let n = self.samples_per_pixel;
+        let h = self.state.next_hash();
+        self.state.dimension += 1;
+
+        let (cols, rows) = grid(n);
+
+        let stratum = permute(self.state.index % n, n, h as u32);
+        let (cx, cy) = (stratum % cols, stratum / cols);
+
+        (
+            (f64::from(cx) + self.rng.gen::<f64>()) / f64::from(cols),
+            (f64::from(cy) + self.rng.gen::<f64>()) / f64::from(rows),
+        )
+    }
+}
+
+impl HaltonSampler {
+    pub fn new(seed: u64) -> Self {
+        HaltonSampler {
+            state: SampleState::new(seed),
+            rng: XorShiftRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Sampler for HaltonSampler {
+    fn start_sample(&mut self, pixel: (u32, u32), index: u32) {
+        self.state.start(pixel, index);
+    }
+
+    fn next_1d(&mut self) -> f64 {
+        let dimension = self.state.dimension as usize;
+        let h = self.state.next_hash();
+
+        match PRIMES.get(dimension) {
+            Some(&base) => {
+                let shift = unit_f64(h);
+                (radical_inverse(base, u64::from(self.state.index)) + shift).fract()
+            }
+            None => self.rng.gen(),
+        }
+    }
+}
+
+impl SobolSampler {
+    pub fn new(seed: u64) -> Self {
+        SobolSampler {
+            state: SampleState::new(seed),
+            rng: XorShiftRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Sampler for SobolSampler {
+    fn start_sample(&mut self, pixel: (u32, u32), index: u32) {
+        self.state.start(pixel, index);
+    }
+
+    fn next_1d(&mut self) -> f64 {
+        let dimension = self.state.dimension as usize;
+        let h = self.state.next_hash();
+
+        if dimension >= SOBOL_DIMENSIONS {
+            return self.rng.gen();
+        }
+
+        let v = sobol(self.state.index, dimension) ^ (h as u32);
+        f64::from(v) / 4_294_967_296.0
+    }
+}
+
+impl SampleState {
+    fn new(seed: u64) -> Self {
+        SampleState {
+            seed,
+            pixel: (0, 0),
+            index: 0,
+            dimension: 0,
+        }
+    }
+
+    fn start(&mut self, pixel: (u32, u32), index: u32) {
+        self.pixel = pixel;
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    /// Return an hash of the pixel and current dimension and advance to the
+    /// next dimension.
+    fn next_hash(&mut self) -> u64 {
+        let h = hash(
+            self.seed
+                ^ hash(u64::from(self.pixel.0) << 32 | u64::from(self.pixel.1))
+                ^ hash(u64::from(self.dimension).wrapping_add(0x9e37_79b9)),
+        );
+        self.dimension += 1;
+        h
+    }
+}
+
+/// Return the columns and rows of the grid, as square as possible, that lays
+/// out exactly `n` strata. Every cell is a stratum so that all of them are
+/// sampled, even if that means a single row when `n` is prime.
+fn grid(n: u32) -> (u32, u32) {
+    let rows = (1..=n)
+        .take_while(|r| r * r <= n)
+        .filter(|r| n.is_multiple_of(*r))
+        .last()
+        .unwrap_or(1);
+
+    (n / rows, rows)
+}
+
+/// [SplitMix64][0] finalizer used as a fast hash function.
+///
+/// [0]: https://xorshift.di.unimi.it/splitmix64.c
+fn hash(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+fn unit_f64(h: u64) -> f64 {
+    (h >> 11) as f64 / (1_u64 << 53) as f64
+}
+
+/// Return the `i`-th element of a random permutation of [0, l) identified by
+/// `p` without storing the permutation. See "Correlated Multi-Jittered
+/// Sampling" by Andrew Kensler.
+fn permute(mut i: u32, l: u32, p: u32) -> u32 {
+    let mut w = l - 1;
+    w |= w >> 1;
+    w |= w >> 2;
+    w |= w >> 4;
+    w |= w >> 8;
+    w |= w >> 16;
+
+    loop {
+        i ^= p;
+        i = i.wrapping_mul(0xe170_893d);
+        i ^= p >> 16;
+        i ^= (i & w) >> 4;
+        i ^= p >> 8;
+        i = i.wrapping_mul(0x0929_eb3f);
+        i ^= p >> 23;
+        i ^= (i & w) >> 1;
+        i = i.wrapping_mul(1 | p >> 27);
+        i = i.wrapping_mul(0x6935_fa69);
+        i ^= (i & w) >> 11;
+        i = i.wrapping_mul(0x74dc_b303);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0x9e50_1cc3);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0xc860_a3df);
+        i &= w;
+        i ^= i >> 5;
+
+        if i < l {
+            break;
+        }
+    }
+
+    (i + p) % l
+}
+
+const PRIMES: [u64; 32] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+    101, 103, 107, 109, 113, 127, 131,
+];
+
+/// Mirror the digits of `i` in the given base around the decimal point.
+fn radical_inverse(base: u64, mut i: u64) -> f64 {
+    let inv_base = 1.0 / base as f64;
+
+    let mut reversed = 0.0;
+    let mut inv_base_n = 1.0;
+    while i > 0 {
+        inv_base_n *= inv_base;
+        reversed += (i % base) as f64 * inv_base_n;
+        i /= base;
+    }
+
+    reversed
+}
+
+const SOBOL_DIMENSIONS: usize = 16;
+
+/// Degree, coefficients and initial direction numbers of the primitive
+/// polynomials of the Sobol dimensions after the first one, taken from the
+/// tables of S. Joe and F. Y. Kuo.
+const SOBOL_POLYNOMIALS: [(u32, u32, &[u32]); SOBOL_DIMENSIONS - 1] = [
+    (1, 0, &[1]),
+    (2, 1, &[1, 3]),
+    (3, 1, &[1, 3, 1]),
+    (3, 2, &[1, 1, 1]),
+    (4, 1, &[1, 1, 3, 3]),
+    (4, 4, &[1, 3, 5, 13]),
+    (5, 2, &[1, 1, 5, 5, 17]),
+    (5, 4, &[1, 1, 5, 5, 5]),
+    (5, 7, &[1, 1, 7, 11, 19]),
+    (5, 11, &[1, 1, 5, 1, 1]),
+    (5, 13, &[1, 1, 1, 3, 11]),
+    (5, 14, &[1, 3, 5, 5, 31]),
+    (6, 1, &[1, 3, 3, 9, 7, 49]),
+    (6, 13, &[1, 1, 1, 15, 21, 21]),
+    (6, 16, &[1, 3, 1, 13, 27, 49]),
+];
+
+/// Calculate the 32 direction numbers of the given Sobol dimension.
+fn sobol_directions(dimension: usize) -> [u32; 32] {
+    let mut v = [0; 32];
+
+    if dimension == 0 {
+        for (k, vk) in v.iter_mut().enumerate() {
+            *vk = 1 << (31 - k);
+        }
+        return v;
+    }
+
+    let (s, a, m) = SOBOL_POLYNOMIALS[dimension - 1];
+    let s = s as usize;
+
+    for k in 0..32 {
+        v[k] = if k < s {
+            m[k] << (31 - k)
+        } else {
+            let mut vk = v[k - s] ^ (v[k - s] >> s);
+            for j in 1..s {
+                if (a >> (s - 1 - j)) & 1 == 1 {
+                    vk ^= v[k - j];
+                }
+            }
+            vk
+        };
+    }
+
+    v
+}
+
+/// Return the `i`-th element of the given Sobol dimension as a 32 bits fixed
+/// point number.
+fn sobol(mut i: u32, dimension: usize) -> u32 {
+    thread_local! {
+        static DIRECTIONS: Vec<[u32; 32]> = (0..SOBOL_DIMENSIONS).map(sobol_directions).collect();
+    }
+
+    DIRECTIONS.with(|directions| {
+        let v = &directions[dimension];
+
+        let mut x = 0;
+        let mut k = 0;
+        while i > 0 {
+            if i & 1 == 1 {
+                x ^= v[k];
+            }
+            i >>= 1;
+            k += 1;
+        }
+
+        x
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_radical_inverse() {
+        assert_eq!(radical_inverse(2, 0), 0.0);
+        assert_eq!(radical_inverse(2, 1), 0.5);
+        assert_eq!(radical_inverse(2, 2), 0.25);
+        assert_eq!(radical_inverse(2, 3), 0.75);
+        assert!((radical_inverse(3, 1) - 1.0 / 3.0).abs() < 1e-12);
+        assert!((radical_inverse(3, 5) - 7.0 / 9.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_sobol() {
+        let to_f64 = |x: u32| f64::from(x) / 4_294_967_296.0;
+
+        let d0 = (0..4).map(|i| to_f64(sobol(i, 0))).collect::<Vec<_>>();
+        assert_eq!(d0, vec![0.0, 0.5, 0.25, 0.75]);
+
+        let d1 = (0..4).map(|i| to_f64(sobol(i, 1))).collect::<Vec<_>>();
+        assert_eq!(d1, vec![0.0, 0.5, 0.75, 0.25]);
+
+        // each dimension is stratified in the first 2^k elements
+        for d in 0..SOBOL_DIMENSIONS {
+            let mut strata = (0..16).map(|i| sobol(i, d) >> 28).collect::<Vec<_>>();
+            strata.sort_unstable();
+            assert_eq!(strata, (0..16).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_permute() {
+        for l in 1..40 {
+            let mut p = (0..l)
+                .map(|i| permute(i, l, 0xdead_beef))
+                .collect::<Vec<_>>();
+            p.sort_unstable();
+            assert_eq!(p, (0..l).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_stratified_sampler() {
+        let mut sampler = StratifiedSampler::new(8, 0);
+
+        let mut strata = (0..8)
+            .map(|i| {
+                sampler.start_sample((3, 7), i);
+                (sampler.next_1d() * 8.0) as u32
+            })
+            .collect::<Vec<_>>();
+        strata.sort_unstable();
+
+        assert_eq!(strata, (0..8).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_stratified_sampler_2d() {
+        assert_eq!(grid(1), (1, 1));
+        assert_eq!(grid(10), (5, 2));
+        assert_eq!(grid(16), (4, 4));
+        assert_eq!(grid(7), (7, 1));
+
+        for n in 1..=20 {
+            let (cols, rows) = grid(n);
+            assert_eq!(cols * rows, n);
+
+            let mut sampler = StratifiedSampler::new(n, 0);
+
+            // every dimension pair covers all the cells of the grid
+            for d in 0..3 {
+                let mut cells = (0..n)
+                    .map(|i| {
+                        sampler.start_sample((3, 7), i);
+                        for _ in 0..d {
+                            sampler.next_2d();
+                        }
+
+                        let (u, v) = sampler.next_2d();
+                        let (cx, cy) = ((u * f64::from(cols)) as u32, (v * f64::from(rows)) as u32);
+                        cy * cols + cx
+                    })
+                    .collect::<Vec<_>>();
+                cells.sort_unstable();
+
+                assert_eq!(cells, (0..n).collect::<Vec<_>>(), "{} samples", n);
+            }
+        }
+    }
+}
@@ -7,12 +7,23 @@ use super::{Error, Mesh, Result};
 /// Obj mesh read from an obj file.
 ///
 /// Currently only a subset of the format is supported that is
-/// only vertices and loops are read. Other features like vertex normals,
-/// texture coordinates and groups are not supported.
+/// only vertices, vertex normals, texture coordinates and loops are read.
+/// Other features like groups and materials are not supported.
 pub struct Obj {
     comments: Vec<String>,
     vertices: Vec<Vec3>,
-    loops: Vec<Vec<isize>>,
+    normals: Vec<Vec3>,
+    uvs: Vec<(f64, f64)>,
+    loops: Vec<Vec<ObjVertex>>,
+}
+
+/// A corner of a loop of an `Obj` made of the 0-based indices of its position,
+/// texture coordinates and normal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObjVertex {
+    pub position: usize,
+    pub uv: Option<usize>,
+    pub normal: Option<usize>,
 }
 
 impl Obj {
@@ -21,6 +32,8 @@ impl Obj {
         let mut mesh = Obj {
             comments: vec![],
             vertices: vec![],
+            normals: vec![],
+            uvs: vec![],
             loops: vec![],
         };
 
@@ -42,26 +55,49 @@ impl Obj {
 
                     mesh.vertices.push(Vec3::new(x, y, z));
                 }
+                "vn" => {
+                    let x = tokens.next().ok_or(Error::BadFormat)?.parse()?;
+                    let y = tokens.next().ok_or(Error::BadFormat)?.parse()?;
+                    let z = tokens.next().ok_or(Error::BadFormat)?.parse()?;
+
+                    mesh.normals.push(Vec3::new(x, y, z));
+                }
+                "vt" => {
+                    let u = tokens.next().ok_or(Error::BadFormat)?.parse()?;
+                    let v = tokens.next().map_or(Ok(0.0), |v| v.parse())?;
+
+                    mesh.uvs.push((u, v));
+                }
                 "f" => {
                     let l = tokens
                         .map(|t| {
-                            // ignore all vertex info, but the vertex index itself
+                            // each vertex is in the form v, v/vt, v//vn or v/vt/vn
                             let mut toks = t.split('/');
-                            let vi = toks.next().ok_or(Error::BadFormat)?.parse()?;
-
-                            // obj is 1-based
-                            if vi == 0 {
-                                return Err(Error::BadFormat);
-                            }
 
-                            Ok(vi)
+                            let position = toks.next().ok_or(Error::BadFormat)?;
+                            let position = resolve_index(position, mesh.vertices.len())?;
+
+                            let uv = match toks.next() {
+                                None | Some("") => None,
+                                Some(i) => Some(resolve_index(i, mesh.uvs.len())?),
+                            };
+                            let normal = match toks.next() {
+                                None | Some("") => None,
+                                Some(i) => Some(resolve_index(i, mesh.normals.len())?),
+                            };
+
+                            Ok(ObjVertex {
+                                position,
+                                uv,
+                                normal,
+                            })
                         })
-                        .collect::<Result<Vec<isize>>>();
+                        .collect::<Result<Vec<_>>>();
                     let l = l?;
 
                     mesh.loops.push(l);
                 }
-                "vn" | "vp" | "vt" | "s" => {
+                "vp" | "s" => {
                     // not supported
                 }
                 _ => return Err(Error::BadFormat),
@@ -70,24 +106,116 @@ impl Obj {
 
         Ok(mesh)
     }
+
+    /// The positions of all the vertices.
+    pub fn vertices(&self) -> &[Vec3] {
+        &self.vertices
+    }
+
+    /// All the vertex normals.
+    pub fn normals(&self) -> &[Vec3] {
+        &self.normals
+    }
+
+    /// All the texture coordinates.
+    pub fn uvs(&self) -> &[(f64, f64)] {
+        &self.uvs
+    }
+
+    /// Return the corners of all the triangular loops of the mesh.
+    ///
+    /// Any non triangular loops are skipped.
+    pub fn faces(&self) -> impl Iterator<Item = [ObjVertex; 3]> + '_ {
+        self.loops.iter().filter_map(|l| match l[..] {
+            [a, b, c] => Some([a, b, c]),
+            _ => None,
+        })
+    }
 }
 
-impl Mesh for Obj {
-    fn triangles(&self) -> Box<dyn Iterator<Item = Triangle> + '_> {
-        Box::new(self.loops.iter().filter_map(move |l| {
-            if l.len() != 3 {
-                return None;
-            }
+/// Convert the 1-based, possibly negative, index of an obj element into a
+/// 0-based index given the number of elements defined so far.
+fn resolve_index(i: &str, len: usize) -> Result<usize> {
+    let i = i.parse::<isize>()?;
+
+    let i = if i > 0 {
+        usize::try_from(i - 1).ok()
+    } else {
+        len.checked_sub(i.unsigned_abs())
+    };
 
-            let get_v = |i: isize| {
-                self.vertices[if i > 0 {
-                    usize::try_from(i - 1).unwrap()
-                } else {
-                    self.vertices.len() - usize::try_from(i.abs()).unwrap()
-                }]
-            };
+    match i {
+        Some(i) if i < len => Ok(i),
+        _ => Err(Error::BadFormat),
+    }
+}
 
-            Some(Triangle::new(get_v(l[0]), get_v(l[1]), get_v(l[2])))
+impl Mesh for Obj {
+    fn triangles(&self) -> Box<dyn Iterator<Item = Triangle> + '_> {
+        Box::new(self.faces().map(move |[a, b, c]| {
+            Triangle::new(
+                self.vertices[a.position],
+                self.vertices[b.position],
+                self.vertices[c.position],
+            )
         }))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load() {
+        let obj = Obj::load(
+            "v 0 0 0
+v 1 0 0
+v 0 1 0
+v 1 1 0
+vt 0 0
+vt 1 1
+vn 0 0 1
+f 1/1/1 2/2/1 3//1
+f -3 -1 -2
+f 1 2 3 4"
+                .as_bytes(),
+        )
+        .unwrap();
+
+        assert_eq!(obj.vertices().len(), 4);
+        assert_eq!(obj.uvs(), &[(0.0, 0.0), (1.0, 1.0)]);
+        assert_eq!(obj.normals(), &[Vec3::new(0.0, 0.0, 1.0)]);
+
+        let faces = obj.faces().collect::<Vec<_>>();
+        assert_eq!(faces.len(), 2);
+        assert_eq!(
+            faces[0],
+            [
+                ObjVertex {
+                    position: 0,
+                    uv: Some(0),
+                    normal: Some(0)
+                },
+                ObjVertex {
+                    position: 1,
+                    uv: Some(1),
+                    normal: Some(0)
+                },
+                ObjVertex {
+                    position: 2,
+                    uv: None,
+                    normal: Some(0)
+                },
+            ]
+        );
+        assert_eq!(
+            faces[1].iter().map(|v| v.position).collect::<Vec<_>>(),
+            vec![1, 3, 2]
+        );
+
+        assert_eq!(obj.triangles().count(), 2);
+
+        assert!(Obj::load("v 0 0 0\nf 1 2 3".as_bytes()).is_err());
+    }
+}
@@ -90,7 +90,7 @@ impl Mat4 {
 
         for r in 0..4 {
             for c in 0..4 {
-                data[r][c] = data[c][r];
+                data[r][c] = self.data[c][r];
             }
         }
 
@@ -143,6 +143,21 @@ impl Mat4 {
         d[0][3]*d[1][2]*d[2][0]*d[3][1] + d[0][3]*d[1][2]*d[2][1]*d[3][0]
     }
 
+    /// Linear interpolation between the coefficients of two matrices. The
+    /// result transforms any point to the linear interpolation of the points
+    /// transformed by `self` and `other`.
+    pub fn lerp(&self, other: &Mat4, t: f64) -> Self {
+        let mut data = [[0.0; 4]; 4];
+
+        for (r, row) in data.iter_mut().enumerate() {
+            for (c, v) in row.iter_mut().enumerate() {
+                *v = self.data[r][c] * (1.0 - t) + other.data[r][c] * t;
+            }
+        }
+
+        Mat4 { data }
+    }
+
     /// Transform the given normalized `Vec3` to another normalized `Vec3`.
     pub fn transform_normal(&self, p: &Vec3) -> Vec3 {
         let dx = self.data[0][0] * p.x + self.data[0][1] * p.y + self.data[0][2] * p.z;
@@ -180,3 +195,40 @@ impl Transform for Vec3 {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transpose() {
+        let m = Mat4::translate(Vec3::new(1.0, 2.0, 3.0));
+        let t = m.transpose();
+
+        assert_eq!(t.data[3], [1.0, 2.0, 3.0, 1.0]);
+        assert_eq!(t.data[0], [1.0, 0.0, 0.0, 0.0]);
+        assert_eq!(t.transpose(), m);
+
+        // normals are transformed by the inverse transpose, stretching along X
+        // makes a diagonal normal lean towards Y
+        let inv = Mat4::scale(Vec3::new(2.0, 1.0, 1.0)).inverse();
+        let n = inv
+            .transpose()
+            .transform_normal(&Vec3::new(1.0, 1.0, 0.0).normalized());
+        assert!(n.dist(Vec3::new(0.5, 1.0, 0.0).normalized()) < 1e-9);
+    }
+
+    #[test]
+    fn test_lerp() {
+        let a = Mat4::translate(Vec3::new(-1.0, 0.0, 0.0));
+        let b =
+            Mat4::translate(Vec3::new(1.0, 2.0, 0.0)).transform(&Mat4::scale(Vec3::replicate(3.0)));
+
+        assert_eq!(a.lerp(&b, 0.0), a);
+        assert_eq!(a.lerp(&b, 1.0), b);
+
+        let p = Vec3::new(1.0, 1.0, 1.0);
+        let mid = p.transform(&a.lerp(&b, 0.25));
+        assert!(mid.dist(p.transform(&a).lerp(p.transform(&b), 0.25)) < 1e-9);
+    }
+}
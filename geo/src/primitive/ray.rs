@@ -10,13 +10,27 @@ pub struct Ray {
 
     /// The direction, possibly not normalized, of the `Ray`.
     pub dir: Vec3,
+
+    /// The instant in time at which the `Ray` was cast. Useful to render
+    /// moving objects.
+    pub time: f64,
 }
 
 impl Ray {
     /// Create a new `Ray` with the given origin and direction. The direction
-    /// doesn't have to be normalized.
+    /// doesn't have to be normalized. The `Ray` is cast at time 0.
     pub fn new(origin: Vec3, dir: Vec3) -> Self {
-        Ray { origin, dir }
+        Ray {
+            origin,
+            dir,
+            time: 0.0,
+        }
+    }
+
+    /// Consume the `Ray` and return a new one cast at the given time.
+    pub fn with_time(mut self, time: f64) -> Self {
+        self.time = time;
+        self
     }
 
     /// Get the point on a `Ray` at the given parameter `t`.
@@ -81,13 +95,13 @@ impl Ray {
 
 impl Transform for Ray {
     fn transform(&self, mat: &Mat4) -> Self {
-        Ray::new(self.origin.transform(mat), mat.transform_normal(&self.dir))
+        Ray::new(self.origin.transform(mat), mat.transform_normal(&self.dir)).with_time(self.time)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Ray, Vec3};
+    use super::{Mat4, Ray, Transform, Vec3};
 
     #[test]
     fn test_point_at() {
@@ -98,6 +112,20 @@ mod tests {
         assert_eq!(ray.point_at(0.5), Vec3::new(0.0, 0.5, 0.0));
     }
 
+    #[test]
+    fn test_with_time() {
+        let ray = Ray::new(Vec3::zero(), Vec3::new(0.0, 1.0, 0.0));
+        assert_eq!(ray.time, 0.0);
+
+        let ray = ray.with_time(0.25);
+        assert_eq!(ray.time, 0.25);
+        assert_eq!(
+            ray.transform(&Mat4::translate(Vec3::new(1.0, 0.0, 0.0)))
+                .time,
+            0.25
+        );
+    }
+
     #[test]
     fn test_reflect() {
         assert_eq!(
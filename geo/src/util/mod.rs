@@ -1 +1,2 @@
 pub mod opener;
+pub mod poly;
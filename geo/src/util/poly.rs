@@ -0,0 +1,170 @@
+//! Solvers for the real roots of polynomials up to the fourth degree.
+
+use std::f64::consts::PI;
+
+const EPS: f64 = 1e-12;
+
+/// Find the real roots of `a x^2 + b x + c = 0` in ascending order.
+pub fn solve_quadratic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    if a.abs() < EPS {
+        if b.abs() < EPS {
+            return vec![];
+        }
+
+        return vec![-c / b];
+    }
+
+    let delta = b.powi(2) - 4.0 * a * c;
+    if delta < 0.0 {
+        return vec![];
+    }
+
+    // avoid the catastrophic cancellation of the textbook formula
+    let q = -0.5 * (b + b.signum() * delta.sqrt());
+    let (x0, x1) = if q.abs() < EPS {
+        (0.0, 0.0)
+    } else {
+        (q / a, c / q)
+    };
+
+    if x0 < x1 {
+        vec![x0, x1]
+    } else {
+        vec![x1, x0]
+    }
+}
+
+/// Find the real roots of `a x^3 + b x^2 + c x + d = 0` in ascending order.
+pub fn solve_cubic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    if a.abs() < EPS {
+        return solve_quadratic(b, c, d);
+    }
+
+    let (a, b, c) = (b / a, c / a, d / a);
+
+    let q = (a.powi(2) - 3.0 * b) / 9.0;
+    let r = (2.0 * a.powi(3) - 9.0 * a * b + 27.0 * c) / 54.0;
+
+    let mut roots = if r.powi(2) < q.powi(3) {
+        let theta = (r / q.powi(3).sqrt()).clamp(-1.0, 1.0).acos();
+        let sq = -2.0 * q.sqrt();
+
+        vec![
+            sq * (theta / 3.0).cos() - a / 3.0,
+            sq * ((theta + 2.0 * PI) / 3.0).cos() - a / 3.0,
+            sq * ((theta - 2.0 * PI) / 3.0).cos() - a / 3.0,
+        ]
+    } else {
+        let aa = -r.signum() * (r.abs() + (r.powi(2) - q.powi(3)).sqrt()).cbrt();
+        let bb = if aa == 0.0 { 0.0 } else { q / aa };
+
+        vec![aa + bb - a / 3.0]
+    };
+
+    roots.sort_by(|x0, x1| x0.partial_cmp(x1).unwrap());
+    roots
+}
+
+/// Find the real roots of `a x^4 + b x^3 + c x^2 + d x + e = 0` in ascending
+/// order using [Ferrari's method][0]. The roots are refined with a couple of
+/// Newton iterations to reduce the numerical error.
+///
+/// [0]: https://en.wikipedia.org/wiki/Quartic_function#Ferrari's_solution
+pub fn solve_quartic(a: f64, b: f64, c: f64, d: f64, e: f64) -> Vec<f64> {
+    if a.abs() < EPS {
+        return solve_cubic(b, c, d, e);
+    }
+
+    let (b, c, d, e) = (b / a, c / a, d / a, e / a);
+
+    // depressed quartic y^4 + p y^2 + q y + r = 0 where x = y - b/4
+    let shift = b / 4.0;
+    let p = c - 3.0 / 8.0 * b.powi(2);
+    let q = d - b * c / 2.0 + b.powi(3) / 8.0;
+    let r = e - b * d / 4.0 + b.powi(2) * c / 16.0 - 3.0 * b.powi(4) / 256.0;
+
+    let mut roots = if q.abs() < EPS {
+        // biquadratic equation
+        solve_quadratic(1.0, p, r)
+            .into_iter()
+            .filter(|z| *z >= 0.0)
+            .flat_map(|z| vec![-z.sqrt(), z.sqrt()])
+            .collect::<Vec<_>>()
+    } else {
+        // the resolvent cubic always has a positive root because it's negative
+        // in 0 and goes to infinity
+        let m = solve_cubic(1.0, p, p.powi(2) / 4.0 - r, -q.powi(2) / 8.0)
+            .into_iter()
+            .fold(0.0, f64::max);
+        let s = (2.0 * m).sqrt();
+
+        let mut roots = solve_quadratic(1.0, -s, p / 2.0 + m + q / (2.0 * s));
+        roots.extend(solve_quadratic(1.0, s, p / 2.0 + m - q / (2.0 * s)));
+        roots
+    };
+
+    for x in &mut roots {
+        *x -= shift;
+
+        for _ in 0..2 {
+            let f = (((*x + b) * *x + c) * *x + d) * *x + e;
+            let df = ((4.0 * *x + 3.0 * b) * *x + 2.0 * c) * *x + d;
+
+            if df.abs() > EPS {
+                *x -= f / df;
+            }
+        }
+    }
+
+    roots.sort_by(|x0, x1| x0.partial_cmp(x1).unwrap());
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_roots(roots: Vec<f64>, expected: &[f64]) {
+        assert_eq!(roots.len(), expected.len(), "{:?}", roots);
+
+        for (r, e) in roots.iter().zip(expected) {
+            assert!((r - e).abs() < 1e-6, "{:?} != {:?}", roots, expected);
+        }
+    }
+
+    #[test]
+    fn test_solve_quadratic() {
+        assert_roots(solve_quadratic(1.0, -3.0, 2.0), &[1.0, 2.0]);
+        assert_roots(solve_quadratic(1.0, 0.0, 1.0), &[]);
+        assert_roots(solve_quadratic(0.0, 2.0, -4.0), &[2.0]);
+    }
+
+    #[test]
+    fn test_solve_cubic() {
+        // (x - 1)(x - 2)(x + 3)
+        assert_roots(solve_cubic(1.0, 0.0, -7.0, 6.0), &[-3.0, 1.0, 2.0]);
+
+        // (x - 2)(x^2 + 1)
+        assert_roots(solve_cubic(2.0, -4.0, 2.0, -4.0), &[2.0]);
+    }
+
+    #[test]
+    fn test_solve_quartic() {
+        // (x - 1)(x - 2)(x - 3)(x + 4)
+        assert_roots(
+            solve_quartic(1.0, -2.0, -13.0, 38.0, -24.0),
+            &[-4.0, 1.0, 2.0, 3.0],
+        );
+
+        // (x^2 - 4)(x^2 - 9)
+        assert_roots(
+            solve_quartic(1.0, 0.0, -13.0, 0.0, 36.0),
+            &[-3.0, -2.0, 2.0, 3.0],
+        );
+
+        // (x^2 + 1)(x - 1)(x - 5)
+        assert_roots(solve_quartic(1.0, -6.0, 6.0, -6.0, 5.0), &[1.0, 5.0]);
+
+        assert_roots(solve_quartic(1.0, 0.0, 1.0, 0.0, 1.0), &[]);
+    }
+}